@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use super::visit::{Visitor, walk_expr, walk_stmt};
+use super::{Expr, Param, Program, Stmt};
+
+/// Checks that parameters with a default come after all the ones without,
+/// and that no call passes the same keyword argument twice. The grammar
+/// already puts keyword arguments after positional ones.
+pub fn check_args(program: &Program) -> Result<(), String> {
+    let mut checker = ArgChecker { error: None };
+    checker.visit_program(program);
+
+    match checker.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct ArgChecker {
+    error: Option<String>,
+}
+
+impl ArgChecker {
+    fn check_params(&mut self, params: &[Param]) {
+        let mut defaulted = false;
+        for param in params {
+            if param.default.is_some() {
+                defaulted = true;
+            } else if defaulted && !param.variadic {
+                self.error = Some(format!(
+                    "parameter `{}` needs a default because an earlier one has one",
+                    param.name
+                ));
+                return;
+            }
+        }
+    }
+
+    fn check_kwargs(&mut self, kwargs: &[(String, Expr)]) {
+        let mut seen = HashSet::new();
+        if let Some((name, _)) = kwargs.iter().find(|(name, _)| !seen.insert(name)) {
+            self.error = Some(format!(
+                "keyword argument `{}` is passed more than once",
+                name
+            ));
+        }
+    }
+}
+
+impl Visitor for ArgChecker {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Stmt::FuncDecl { params, .. } = stmt {
+            self.check_params(params);
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match expr {
+            Expr::Lambda { params, .. } => self.check_params(params),
+            Expr::FuncCall { kwargs, .. }
+            | Expr::MethodCall { kwargs, .. }
+            | Expr::Call { kwargs, .. } => self.check_kwargs(kwargs),
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    // `name: i64 = default`
+    fn defaulted(name: &str, default: i64) -> Param {
+        Param {
+            default: Some(int(default)),
+            ..param(name)
+        }
+    }
+
+    fn call_with(kwargs: &[&str]) -> Stmt {
+        let kwargs = kwargs
+            .iter()
+            .map(|name| (name.to_string(), int(1)))
+            .collect();
+        Stmt::ExprStmt(Expr::FuncCall {
+            name: "f".to_string(),
+            args: Vec::new(),
+            kwargs,
+        })
+    }
+
+    fn check(statements: Vec<Stmt>) -> Result<(), String> {
+        check_args(&Program { statements })
+    }
+
+    #[test]
+    fn defaults_come_last() {
+        let rest = Param {
+            variadic: true,
+            ..param("rest")
+        };
+        let params = vec![param("a"), defaulted("b", 1), rest];
+        assert_eq!(check(vec![func("f", params, Vec::new())]), Ok(()));
+
+        let params = vec![defaulted("a", 1), param("b")];
+        assert_eq!(
+            check(vec![func("f", params, Vec::new())]),
+            Err("parameter `b` needs a default because an earlier one has one".to_string())
+        );
+    }
+
+    #[test]
+    fn keyword_arguments_are_unique() {
+        assert_eq!(check(vec![call_with(&["a", "b"])]), Ok(()));
+        assert_eq!(
+            check(vec![call_with(&["a", "b", "a"])]),
+            Err("keyword argument `a` is passed more than once".to_string())
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+
+use super::visit::{Visitor, walk_block, walk_expr, walk_stmt};
+use super::{CompClause, Expr, Param, Program, Stmt};
+
+/// Checks that no name is declared twice in the same scope and that no
+/// assignment targets a `const`. Declaring a name again in an inner scope
+/// shadows the outer one and is allowed; a variable, parameter or loop
+/// variable that hides a const that way may be assigned as usual. `_` may
+/// be declared any number of times.
+pub fn check_bindings(program: &Program) -> Result<(), String> {
+    let mut checker = BindingChecker {
+        scopes: vec![HashMap::new()],
+        error: None,
+    };
+    checker.visit_program(program);
+
+    match checker.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct BindingChecker {
+    // name -> what declared it: "constant", "variable", "parameter", ...
+    scopes: Vec<HashMap<String, &'static str>>,
+    error: Option<String>,
+}
+
+impl BindingChecker {
+    fn declare<'a>(&mut self, names: impl IntoIterator<Item = &'a String>, kind: &'static str) {
+        let scope = self.scopes.last_mut().unwrap();
+        for name in names {
+            if let Some(earlier) = scope.insert(name.clone(), kind)
+                && name != "_"
+            {
+                self.error = Some(format!(
+                    "{} `{}` is already declared in this scope",
+                    earlier, name
+                ));
+                return;
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&'static str> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+
+    // `a = ...` and each name in `(a, b) = ...`
+    fn check_target(&mut self, target: &Expr) {
+        match target {
+            Expr::Variable(name) if self.lookup(name) == Some("constant") => {
+                self.error = Some(format!("cannot assign to const `{}`", name));
+            }
+            Expr::Tuple(targets) => {
+                for target in targets {
+                    self.check_target(target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_params(&mut self, params: &[Param]) {
+        for default in params.iter().filter_map(|param| param.default.as_ref()) {
+            self.visit_expr(default);
+        }
+        self.declare(params.iter().map(|param| &param.name), "parameter");
+    }
+
+    fn visit_comp(&mut self, clause: &CompClause, results: &[&Expr]) {
+        self.visit_expr(&clause.iter);
+        self.scopes.push(HashMap::new());
+        self.declare(
+            std::iter::once(&clause.var).chain(&clause.value_var),
+            "loop variable",
+        );
+        if let Some(condition) = &clause.condition {
+            self.visit_expr(condition);
+        }
+        for result in results {
+            self.visit_expr(result);
+        }
+        self.scopes.pop();
+    }
+}
+
+impl Visitor for BindingChecker {
+    // The program's statements share the outermost scope
+    fn visit_program(&mut self, program: &Program) {
+        walk_block(self, &program.statements);
+    }
+
+    fn visit_block(&mut self, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        walk_block(self, stmts);
+        self.scopes.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match stmt {
+            Stmt::VariableDecl { name, .. } => {
+                walk_stmt(self, stmt);
+                self.declare([name], "variable");
+            }
+            Stmt::ConstDecl { name, .. } => {
+                walk_stmt(self, stmt);
+                self.declare([name], "constant");
+            }
+            Stmt::TupleDecl { names, .. } => {
+                walk_stmt(self, stmt);
+                self.declare(names, "variable");
+            }
+            Stmt::FuncDecl { params, body, .. } => {
+                self.scopes.push(HashMap::new());
+                self.visit_params(params);
+                self.visit_block(body);
+                self.scopes.pop();
+            }
+            Stmt::For {
+                var,
+                value_var,
+                iter_expr,
+                step,
+                body,
+                ..
+            } => {
+                self.visit_expr(iter_expr);
+                if let Some(step) = step {
+                    self.visit_expr(step);
+                }
+                self.scopes.push(HashMap::new());
+                self.declare(std::iter::once(var).chain(value_var), "loop variable");
+                self.visit_block(body);
+                self.scopes.pop();
+            }
+            Stmt::Try {
+                body,
+                error_var,
+                handler,
+            } => {
+                self.visit_block(body);
+                self.scopes.push(HashMap::new());
+                self.declare(error_var, "error variable");
+                self.visit_block(handler);
+                self.scopes.pop();
+            }
+            Stmt::Assignment { target, .. } => {
+                self.check_target(target);
+                walk_stmt(self, stmt);
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match expr {
+            Expr::Lambda { params, body } => {
+                self.scopes.push(HashMap::new());
+                self.visit_params(params);
+                self.visit_expr(body);
+                self.scopes.pop();
+            }
+            Expr::ListComp { element, clause } => self.visit_comp(clause, &[element]),
+            Expr::MapComp { key, value, clause } => self.visit_comp(clause, &[key, value]),
+            // The value can see the block's own declarations
+            Expr::Do { body, value } => {
+                self.scopes.push(HashMap::new());
+                walk_block(self, body);
+                self.visit_expr(value);
+                self.scopes.pop();
+            }
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    fn assign(target: Expr, value: Expr) -> Stmt {
+        Stmt::Assignment { target, value }
+    }
+
+    fn check(statements: Vec<Stmt>) -> Result<(), String> {
+        check_bindings(&Program { statements })
+    }
+
+    #[test]
+    fn rejects_assignment_to_consts() {
+        let statements = vec![konst("N", int(1)), assign(var("N"), int(2))];
+        assert_eq!(
+            check(statements),
+            Err("cannot assign to const `N`".to_string())
+        );
+
+        let statements = vec![
+            konst("N", int(1)),
+            let_("a", int(0)),
+            func(
+                "f",
+                Vec::new(),
+                vec![assign(Expr::Tuple(vec![var("a"), var("N")]), var("pair"))],
+            ),
+        ];
+        assert_eq!(
+            check(statements),
+            Err("cannot assign to const `N`".to_string())
+        );
+    }
+
+    #[test]
+    fn inner_bindings_hide_consts() {
+        let statements = vec![
+            konst("N", int(1)),
+            Stmt::Block(vec![let_("N", int(2)), assign(var("N"), int(3))]),
+            func("f", vec![param("N")], vec![assign(var("N"), int(4))]),
+            for_("N", vec![assign(var("N"), int(5))]),
+        ];
+        assert_eq!(check(statements), Ok(()));
+    }
+
+    #[test]
+    fn rejects_redeclaration_in_the_same_scope() {
+        let cases = [
+            (
+                vec![let_("x", int(1)), let_("x", int(2))],
+                "variable `x` is already declared in this scope",
+            ),
+            (
+                vec![konst("N", int(1)), let_("N", int(2))],
+                "constant `N` is already declared in this scope",
+            ),
+            (
+                vec![Stmt::TupleDecl {
+                    names: vec!["a".to_string(), "a".to_string()],
+                    expr: var("pair"),
+                }],
+                "variable `a` is already declared in this scope",
+            ),
+            (
+                vec![func("f", vec![param("a"), param("a")], Vec::new())],
+                "parameter `a` is already declared in this scope",
+            ),
+            (
+                vec![Stmt::ExprStmt(lambda(&["a", "a"], var("a")))],
+                "parameter `a` is already declared in this scope",
+            ),
+            (
+                vec![Stmt::For {
+                    label: None,
+                    var: "k".to_string(),
+                    value_var: Some("k".to_string()),
+                    iter_expr: var("map"),
+                    step: None,
+                    body: Vec::new(),
+                }],
+                "loop variable `k` is already declared in this scope",
+            ),
+            (
+                vec![Stmt::ExprStmt(do_block(
+                    vec![let_("x", int(1)), let_("x", int(2))],
+                    var("x"),
+                ))],
+                "variable `x` is already declared in this scope",
+            ),
+        ];
+        for (statements, error) in cases {
+            assert_eq!(check(statements), Err(error.to_string()));
+        }
+
+        let comp = Expr::ListComp {
+            element: Box::new(var("k")),
+            clause: CompClause {
+                var: "k".to_string(),
+                value_var: Some("k".to_string()),
+                iter: Box::new(var("xs")),
+                condition: None,
+            },
+        };
+        assert_eq!(
+            check(vec![Stmt::ExprStmt(comp)]),
+            Err("loop variable `k` is already declared in this scope".to_string())
+        );
+    }
+
+    #[test]
+    fn inner_scopes_may_shadow() {
+        let statements = vec![
+            let_("x", int(1)),
+            let_("_", int(2)),
+            let_("_", int(3)),
+            Stmt::Block(vec![let_("x", int(4))]),
+            func("f", vec![param("x")], vec![let_("x", int(5))]),
+            for_("x", vec![let_("x", int(6))]),
+            Stmt::Try {
+                body: vec![let_("x", int(7))],
+                error_var: Some("x".to_string()),
+                handler: vec![let_("x", int(8))],
+            },
+            Stmt::ExprStmt(lambda(&["x"], do_block(vec![let_("x", int(9))], var("x")))),
+        ];
+        assert_eq!(check(statements), Ok(()));
+    }
+
+    #[test]
+    fn consts_stay_in_their_block() {
+        let statements = vec![
+            let_("N", int(1)),
+            Stmt::Block(vec![konst("N", int(2))]),
+            assign(var("N"), int(3)),
+        ];
+        assert_eq!(check(statements), Ok(()));
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use super::optimize::{fold_binary, fold_chain, fold_unary, int_error, too_long};
+use super::visit::{Visitor, walk_block, walk_expr, walk_stmt};
+use super::{CompClause, Expr, Literal, Param, Program, Stmt};
+
+const NOT_CONSTANT: &str = "is not a constant expression";
+
+/// Evaluates every `const` initializer in declaration order and returns the
+/// top-level values by name. Initializers may use literals, arithmetic,
+/// comparison and boolean operators, and constants declared earlier in an
+/// enclosing scope; any other expression is rejected with the offending
+/// constant's name, as is an integer operation that overflows or divides
+/// by zero and a string repeated past the folding limit.
+pub fn eval_consts(program: &Program) -> Result<HashMap<String, Literal>, String> {
+    let mut evaluator = ConstEvaluator {
+        scopes: vec![HashMap::new()],
+        error: None,
+    };
+    evaluator.visit_program(program);
+
+    if let Some(error) = evaluator.error {
+        return Err(error);
+    }
+    let top_level = evaluator.scopes.pop().unwrap();
+    Ok(top_level
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect())
+}
+
+struct ConstEvaluator {
+    // `None` marks a runtime binding that hides a const of the same name
+    scopes: Vec<HashMap<String, Option<Literal>>>,
+    error: Option<String>,
+}
+
+impl ConstEvaluator {
+    fn lookup(&self, name: &str) -> Option<&Literal> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))?
+            .as_ref()
+    }
+
+    fn bind<'a>(&mut self, names: impl IntoIterator<Item = &'a String>) {
+        let scope = self.scopes.last_mut().unwrap();
+        for name in names {
+            scope.insert(name.clone(), None);
+        }
+    }
+
+    fn visit_params(&mut self, params: &[Param]) {
+        for default in params.iter().filter_map(|param| param.default.as_ref()) {
+            self.visit_expr(default);
+        }
+        self.bind(params.iter().map(|param| &param.name));
+    }
+
+    fn visit_comp(&mut self, clause: &CompClause, results: &[&Expr]) {
+        self.visit_expr(&clause.iter);
+        self.scopes.push(HashMap::new());
+        self.bind(std::iter::once(&clause.var).chain(&clause.value_var));
+        if let Some(condition) = &clause.condition {
+            self.visit_expr(condition);
+        }
+        for result in results {
+            self.visit_expr(result);
+        }
+        self.scopes.pop();
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Literal, &'static str> {
+        match expr {
+            Expr::Literal(lit) => Ok(lit.clone()),
+            Expr::Variable(name) => self.lookup(name).cloned().ok_or(NOT_CONSTANT),
+            Expr::Grouped(inner) => self.eval(inner),
+            Expr::UnaryOp { op, expr } => {
+                let value = self.eval(expr)?;
+                fold_unary(op, &value).ok_or(match value {
+                    Literal::Int(_) if op == "-" => "overflows i64",
+                    _ => NOT_CONSTANT,
+                })
+            }
+            Expr::BinaryOp { left, op, right } => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                fold_binary(&left, op, &right).ok_or_else(|| {
+                    if too_long(&left, op, &right) {
+                        "is too large"
+                    } else {
+                        int_error(&left, op, &right).unwrap_or(NOT_CONSTANT)
+                    }
+                })
+            }
+            Expr::Compare { first, rest } => {
+                let mut error = None;
+                let first = self.eval(first)?;
+                let folded = fold_chain(first, rest, |operand| {
+                    self.eval(operand).map_err(|e| error = Some(e)).ok()
+                });
+                folded.ok_or(error.unwrap_or(NOT_CONSTANT))
+            }
+            _ => Err(NOT_CONSTANT),
+        }
+    }
+}
+
+impl Visitor for ConstEvaluator {
+    // The program's statements share the outermost scope, which is returned
+    fn visit_program(&mut self, program: &Program) {
+        walk_block(self, &program.statements);
+    }
+
+    fn visit_block(&mut self, stmts: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        walk_block(self, stmts);
+        self.scopes.pop();
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match stmt {
+            Stmt::ConstDecl { name, expr, .. } => match self.eval(expr) {
+                Ok(value) => {
+                    let scope = self.scopes.last_mut().unwrap();
+                    scope.insert(name.clone(), Some(value));
+                }
+                Err(reason) => {
+                    self.error = Some(format!("initializer of const `{}` {}", name, reason));
+                }
+            },
+            Stmt::VariableDecl { name, .. } => {
+                walk_stmt(self, stmt);
+                self.bind([name]);
+            }
+            Stmt::TupleDecl { names, .. } => {
+                walk_stmt(self, stmt);
+                self.bind(names);
+            }
+            Stmt::FuncDecl { params, body, .. } => {
+                self.scopes.push(HashMap::new());
+                self.visit_params(params);
+                self.visit_block(body);
+                self.scopes.pop();
+            }
+            Stmt::For {
+                var,
+                value_var,
+                iter_expr,
+                step,
+                body,
+                ..
+            } => {
+                self.visit_expr(iter_expr);
+                if let Some(step) = step {
+                    self.visit_expr(step);
+                }
+                self.scopes.push(HashMap::new());
+                self.bind(std::iter::once(var).chain(value_var));
+                self.visit_block(body);
+                self.scopes.pop();
+            }
+            Stmt::Try {
+                body,
+                error_var,
+                handler,
+            } => {
+                self.visit_block(body);
+                self.scopes.push(HashMap::new());
+                self.bind(error_var);
+                self.visit_block(handler);
+                self.scopes.pop();
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lambda { params, body } => {
+                self.scopes.push(HashMap::new());
+                self.visit_params(params);
+                self.visit_expr(body);
+                self.scopes.pop();
+            }
+            Expr::ListComp { element, clause } => self.visit_comp(clause, &[element]),
+            Expr::MapComp { key, value, clause } => self.visit_comp(clause, &[key, value]),
+            _ => walk_expr(self, expr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    fn eval(statements: Vec<Stmt>) -> Result<HashMap<String, Literal>, String> {
+        eval_consts(&Program { statements })
+    }
+
+    #[test]
+    fn uses_earlier_consts() {
+        let values = eval(vec![
+            konst("N", int(2)),
+            konst("M", binary(var("N"), "*", int(21))),
+        ])
+        .unwrap();
+        assert!(matches!(values["M"], Literal::Int(42)));
+    }
+
+    #[test]
+    fn nested_consts_stay_in_their_block() {
+        let statements = vec![
+            Stmt::Block(vec![konst("N", int(2))]),
+            konst("M", binary(var("N"), "+", int(1))),
+        ];
+        assert_eq!(
+            eval(statements).unwrap_err(),
+            "initializer of const `M` is not a constant expression"
+        );
+
+        let values = eval(vec![
+            konst("N", int(1)),
+            Stmt::Block(vec![konst("N", int(2)), konst("M", var("N"))]),
+        ])
+        .unwrap();
+        assert_eq!(values.len(), 1);
+        assert!(matches!(values["N"], Literal::Int(1)));
+    }
+
+    #[test]
+    fn runtime_bindings_hide_consts() {
+        let statements = vec![
+            konst("N", int(1)),
+            Stmt::Block(vec![let_("N", int(5)), konst("M", var("N"))]),
+        ];
+        assert_eq!(
+            eval(statements).unwrap_err(),
+            "initializer of const `M` is not a constant expression"
+        );
+    }
+
+    #[test]
+    fn reports_integer_errors() {
+        assert_eq!(
+            eval(vec![konst("Z", binary(int(1), "/", int(0)))]).unwrap_err(),
+            "initializer of const `Z` divides by zero"
+        );
+        assert_eq!(
+            eval(vec![konst("Z", binary(int(i64::MAX), "+", int(1)))]).unwrap_err(),
+            "initializer of const `Z` overflows i64"
+        );
+    }
+
+    #[test]
+    fn repeats_long_strings() {
+        let pad = binary(string("-"), "*", int(5000));
+        let values = eval(vec![konst("PAD", pad)]).unwrap();
+        assert!(matches!(&values["PAD"], Literal::String(s) if s.len() == 5000));
+    }
+
+    #[test]
+    fn refuses_huge_repeats() {
+        let pad = binary(string("ab"), "*", int(1_000_000_000_000));
+        assert_eq!(
+            eval(vec![konst("PAD", pad)]).unwrap_err(),
+            "initializer of const `PAD` is too large"
+        );
+    }
+}
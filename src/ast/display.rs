@@ -0,0 +1,645 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{CompClause, Expr, Literal, MapEntry, Param, Program, Stmt};
+
+// Binding strength of each expression form, mirroring the grammar's rule
+// chain; a child printed where a higher level is required gets parentheses.
+const LAMBDA: u8 = 0;
+const COMPARE: u8 = 4;
+const UNARY: u8 = 8;
+const POSTFIX: u8 = 9;
+const ATOM: u8 = 10;
+
+fn binary_precedence(op: &str) -> u8 {
+    match op {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" => 3,
+        "<" | "<=" | ">" | ">=" => COMPARE,
+        ".." | "..=" => 5,
+        "+" | "-" => 6,
+        "*" | "/" | "%" => 7,
+        _ => 1,
+    }
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Lambda { .. } | Expr::Spread(_) => LAMBDA,
+        Expr::BinaryOp { op, .. } => binary_precedence(op),
+        Expr::Compare { .. } => COMPARE,
+        Expr::UnaryOp { .. } | Expr::Ref { .. } => UNARY,
+        // Values without a literal of their own print as the arithmetic
+        // that produces them
+        Expr::Literal(Literal::Int(i64::MIN)) => binary_precedence("-"),
+        Expr::Literal(Literal::Float(n)) if !n.is_finite() => binary_precedence("/"),
+        // A negative literal prints with a leading `-`, just like negation
+        Expr::Literal(Literal::Int(n)) if *n < 0 => UNARY,
+        Expr::Literal(Literal::Float(n)) if n.is_sign_negative() => UNARY,
+        Expr::FuncCall { .. }
+        | Expr::MethodCall { .. }
+        | Expr::Call { .. }
+        | Expr::FieldAccess { .. }
+        | Expr::ArrayAccess { .. } => POSTFIX,
+        Expr::Grouped(inner) => precedence(inner),
+        _ => ATOM,
+    }
+}
+
+fn write_expr(f: &mut Formatter, expr: &Expr, min: u8) -> fmt::Result {
+    if precedence(expr) < min {
+        f.write_char('(')?;
+        write_expr(f, expr, LAMBDA)?;
+        return f.write_char(')');
+    }
+
+    match expr {
+        Expr::Literal(lit) => write!(f, "{}", lit),
+        Expr::Variable(name) => f.write_str(name),
+        Expr::UnaryOp { op, expr } => {
+            f.write_str(op)?;
+            write_expr(f, expr, UNARY)
+        }
+        Expr::Ref { mutable, expr } => {
+            f.write_str(if *mutable { "&mut " } else { "&" })?;
+            write_expr(f, expr, UNARY)
+        }
+        Expr::BinaryOp { left, op, right } => {
+            let prec = binary_precedence(op);
+            // Comparisons chain (`a < b < c`), so a nested one on the left
+            // must keep its parentheses to mean the same thing
+            let left_min = if prec == COMPARE { prec + 1 } else { prec };
+            write_expr(f, left, left_min)?;
+            write!(f, " {} ", op)?;
+            write_expr(f, right, prec + 1)
+        }
+        Expr::Compare { first, rest } => {
+            write_expr(f, first, COMPARE + 1)?;
+            for (op, operand) in rest {
+                write!(f, " {} ", op)?;
+                write_expr(f, operand, COMPARE + 1)?;
+            }
+            Ok(())
+        }
+        Expr::FuncCall { name, args, kwargs } => {
+            f.write_str(name)?;
+            write_args(f, args, kwargs)
+        }
+        Expr::MethodCall {
+            object,
+            method,
+            args,
+            kwargs,
+        } => {
+            write_expr(f, object, POSTFIX)?;
+            write!(f, ".{}", method)?;
+            write_args(f, args, kwargs)
+        }
+        Expr::Call {
+            callee,
+            args,
+            kwargs,
+        } => {
+            write_expr(f, callee, POSTFIX)?;
+            write_args(f, args, kwargs)
+        }
+        Expr::FieldAccess { object, field } => {
+            write_expr(f, object, POSTFIX)?;
+            write!(f, ".{}", field)
+        }
+        Expr::ArrayAccess { object, index } => {
+            write_expr(f, object, POSTFIX)?;
+            f.write_char('[')?;
+            write_expr(f, index, LAMBDA)?;
+            f.write_char(']')
+        }
+        Expr::ArrayLiteral(elements) => {
+            f.write_char('[')?;
+            write_list(f, elements)?;
+            f.write_char(']')
+        }
+        Expr::MapLiteral(entries) => {
+            f.write_char('{')?;
+            for (i, entry) in entries.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                match entry {
+                    MapEntry::Pair(key, value) => {
+                        write_expr(f, key, LAMBDA + 1)?;
+                        f.write_str(": ")?;
+                        write_expr(f, value, LAMBDA)?;
+                    }
+                    MapEntry::Spread(base) => {
+                        write_expr(f, base, LAMBDA + 1)?;
+                        f.write_str("...")?;
+                    }
+                }
+            }
+            f.write_char('}')
+        }
+        Expr::Tuple(elements) => {
+            f.write_char('(')?;
+            write_list(f, elements)?;
+            if elements.len() == 1 {
+                f.write_char(',')?;
+            }
+            f.write_char(')')
+        }
+        Expr::ListComp { element, clause } => {
+            f.write_char('[')?;
+            write_expr(f, element, LAMBDA)?;
+            write!(f, "{}]", clause)
+        }
+        Expr::MapComp { key, value, clause } => {
+            f.write_char('{')?;
+            write_expr(f, key, LAMBDA + 1)?;
+            f.write_str(": ")?;
+            write_expr(f, value, LAMBDA)?;
+            write!(f, "{}}}", clause)
+        }
+        Expr::Grouped(inner) => write_expr(f, inner, min),
+        Expr::Spread(inner) => {
+            write_expr(f, inner, LAMBDA + 1)?;
+            f.write_str("...")
+        }
+        Expr::Lambda { params, body } => {
+            f.write_char('|')?;
+            for (i, param) in params.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", param)?;
+            }
+            f.write_str("| ")?;
+            write_expr(f, body, LAMBDA)
+        }
+        // Kept on one line since expressions carry no indentation
+        Expr::Do { body, value } => {
+            f.write_str("do { ")?;
+            for stmt in body {
+                write_stmt(f, stmt, 0)?;
+                f.write_char(' ')?;
+            }
+            write!(f, "{} }}", value)
+        }
+    }
+}
+
+fn write_list(f: &mut Formatter, exprs: &[Expr]) -> fmt::Result {
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write_expr(f, expr, LAMBDA)?;
+    }
+    Ok(())
+}
+
+fn write_args(f: &mut Formatter, args: &[Expr], kwargs: &[(String, Expr)]) -> fmt::Result {
+    f.write_char('(')?;
+    write_list(f, args)?;
+    for (i, (name, value)) in kwargs.iter().enumerate() {
+        if i > 0 || !args.is_empty() {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}: ", name)?;
+        write_expr(f, value, LAMBDA)?;
+    }
+    f.write_char(')')
+}
+
+fn write_stmt(f: &mut Formatter, stmt: &Stmt, indent: usize) -> fmt::Result {
+    let pad = "    ".repeat(indent);
+
+    match stmt {
+        Stmt::VariableDecl { name, ty, expr } => {
+            write!(f, "{}let {}", pad, name)?;
+            if let Some(ty) = ty {
+                write!(f, ": {}", ty)?;
+            }
+            // The grammar always wants an initializer
+            match expr {
+                Some(expr) => write!(f, " = {};", expr),
+                None => f.write_str(" = nil;"),
+            }
+        }
+        Stmt::TupleDecl { names, expr } => {
+            write!(f, "{}let ({}) = {};", pad, names.join(", "), expr)
+        }
+        Stmt::ConstDecl { name, ty, expr } => {
+            write!(f, "{}const {}: {} = {};", pad, name, ty, expr)
+        }
+        Stmt::FuncDecl {
+            doc,
+            name,
+            params,
+            return_type,
+            body,
+        } => {
+            write_doc(f, doc, &pad)?;
+            write!(f, "{}func {}(", pad, name)?;
+            for (i, param) in params.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", param)?;
+            }
+            f.write_char(')')?;
+            if let Some(return_type) = return_type {
+                write!(f, " -> {}", return_type)?;
+            }
+            f.write_char(' ')?;
+            write_block(f, body, indent)
+        }
+        Stmt::StructDecl { doc, name, fields } => {
+            write_doc(f, doc, &pad)?;
+            writeln!(f, "{}struct {} {{", pad, name)?;
+            for (field, ty) in fields {
+                writeln!(f, "{}    {}: {},", pad, field, ty)?;
+            }
+            write!(f, "{}}}", pad)
+        }
+        Stmt::ImplDecl { type_name, methods } => {
+            write!(f, "{}impl {} ", pad, type_name)?;
+            write_block(f, methods, indent)
+        }
+        Stmt::Return(expr) => write!(f, "{}ret {};", pad, expr),
+        Stmt::Raise(expr) => write!(f, "{}raise {};", pad, expr),
+        Stmt::Yield(expr) => write!(f, "{}yield {};", pad, expr),
+        Stmt::Break(label) => write_jump(f, &pad, "break", label),
+        Stmt::Continue(label) => write_jump(f, &pad, "continue", label),
+        Stmt::Assignment { target, value } => write!(f, "{}{} = {};", pad, target, value),
+        Stmt::ExprStmt(expr) => write!(f, "{}{};", pad, expr),
+        // There is no bare block statement; an always-taken `if` scopes the same way
+        Stmt::Block(body) => {
+            write!(f, "{}if true ", pad)?;
+            write_block(f, body, indent)
+        }
+        Stmt::If { .. } => {
+            f.write_str(&pad)?;
+            write_if(f, stmt, indent)
+        }
+        Stmt::While {
+            label,
+            condition,
+            body,
+        } => {
+            write!(f, "{}{}while {} ", pad, Label(label), condition)?;
+            write_block(f, body, indent)
+        }
+        Stmt::Loop { label, body } => {
+            write!(f, "{}{}loop ", pad, Label(label))?;
+            write_block(f, body, indent)
+        }
+        Stmt::For {
+            label,
+            var,
+            value_var,
+            iter_expr,
+            step,
+            body,
+        } => {
+            write!(f, "{}{}for {}", pad, Label(label), var)?;
+            if let Some(value_var) = value_var {
+                write!(f, ", {}", value_var)?;
+            }
+            write!(f, " in {} ", iter_expr)?;
+            if let Some(step) = step {
+                write!(f, "step {} ", step)?;
+            }
+            write_block(f, body, indent)
+        }
+        Stmt::Switch {
+            expr,
+            cases,
+            default,
+        } => {
+            writeln!(f, "{}switch {} {{", pad, expr)?;
+            for (value, body) in cases {
+                writeln!(f, "{}    case {}:", pad, value)?;
+                write_stmts(f, body, indent + 2)?;
+            }
+            if let Some(default) = default {
+                writeln!(f, "{}    default:", pad)?;
+                write_stmts(f, default, indent + 2)?;
+            }
+            write!(f, "{}}}", pad)
+        }
+        Stmt::Try {
+            body,
+            error_var,
+            handler,
+        } => {
+            write!(f, "{}try ", pad)?;
+            write_block(f, body, indent)?;
+            f.write_str(" catch ")?;
+            if let Some(error_var) = error_var {
+                write!(f, "{} ", error_var)?;
+            }
+            write_block(f, handler, indent)
+        }
+    }
+}
+
+// Writes `if ... { } elif ... { } else { }` without the leading indent, so
+// an `else` holding a single `if` can continue the chain. `else if` parses
+// the same, but `elif` is the canonical spelling.
+fn write_if(f: &mut Formatter, stmt: &Stmt, indent: usize) -> fmt::Result {
+    let Stmt::If {
+        condition,
+        then_branch,
+        else_branch,
+    } = stmt
+    else {
+        unreachable!("write_if called with a non-if statement");
+    };
+
+    write!(f, "if {} ", condition)?;
+    write_block(f, then_branch, indent)?;
+
+    match else_branch.as_deref() {
+        Some([nested @ Stmt::If { .. }]) => {
+            f.write_str(" el")?;
+            write_if(f, nested, indent)
+        }
+        Some(else_branch) => {
+            f.write_str(" else ")?;
+            write_block(f, else_branch, indent)
+        }
+        None => Ok(()),
+    }
+}
+
+fn write_jump(f: &mut Formatter, pad: &str, keyword: &str, label: &Option<String>) -> fmt::Result {
+    match label {
+        Some(label) => write!(f, "{}{} {};", pad, keyword, label),
+        None => write!(f, "{}{};", pad, keyword),
+    }
+}
+
+// Writes `label: ` in front of a loop, or nothing
+struct Label<'a>(&'a Option<String>);
+
+impl Display for Label<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.0 {
+            Some(label) => write!(f, "{}: ", label),
+            None => Ok(()),
+        }
+    }
+}
+
+fn write_block(f: &mut Formatter, stmts: &[Stmt], indent: usize) -> fmt::Result {
+    if stmts.is_empty() {
+        return f.write_str("{}");
+    }
+    f.write_str("{\n")?;
+    write_stmts(f, stmts, indent + 1)?;
+    write!(f, "{}}}", "    ".repeat(indent))
+}
+
+fn write_stmts(f: &mut Formatter, stmts: &[Stmt], indent: usize) -> fmt::Result {
+    for stmt in stmts {
+        write_stmt(f, stmt, indent)?;
+        f.write_char('\n')?;
+    }
+    Ok(())
+}
+
+fn write_doc(f: &mut Formatter, doc: &Option<String>, pad: &str) -> fmt::Result {
+    for line in doc.iter().flat_map(|doc| doc.lines()) {
+        if line.is_empty() {
+            writeln!(f, "{}##", pad)?;
+        } else {
+            writeln!(f, "{}## {}", pad, line)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_escaped(f: &mut Formatter, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\0' => f.write_str("\\0")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+// Debug gives the shortest digits that round-trip, but drops the fraction
+// in exponent form (`1e16`), which would read back as an integer. Infinity
+// and NaN print as the division that produces them.
+fn write_float(f: &mut Formatter, n: f64) -> fmt::Result {
+    if n.is_nan() {
+        return f.write_str("0.0 / 0.0");
+    } else if n.is_infinite() {
+        let sign = if n < 0.0 { "-" } else { "" };
+        return write!(f, "{}1.0 / 0.0", sign);
+    }
+
+    let digits = format!("{:?}", n);
+    match digits.find('e') {
+        Some(exp) if !digits[..exp].contains('.') => {
+            write!(f, "{}.0{}", &digits[..exp], &digits[exp..])
+        }
+        _ => f.write_str(&digits),
+    }
+}
+
+/// Prints source that parses back to an equivalent program, using only the
+/// parentheses that operator precedence requires. The tree read back may
+/// differ where the language has no literal spelling: negative numbers
+/// come back as a negation, and `i64::MIN` and non-finite floats as
+/// arithmetic.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_expr(f, self, LAMBDA)
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Literal::Int(i64::MIN) => write!(f, "{} - 1", i64::MIN + 1),
+            Literal::Int(n) => write!(f, "{}", n),
+            Literal::Float(n) => write_float(f, *n),
+            Literal::String(s) => write_escaped(f, s),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Null => f.write_str("nil"),
+        }
+    }
+}
+
+// Includes the leading space: ` for k, v in iter if cond`
+impl Display for CompClause {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, " for {}", self.var)?;
+        if let Some(value_var) = &self.value_var {
+            write!(f, ", {}", value_var)?;
+        }
+        write!(f, " in {}", self.iter)?;
+        if let Some(condition) = &self.condition {
+            write!(f, " if {}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(ty) = &self.ty {
+            write!(f, ": {}", ty)?;
+        }
+        if self.variadic {
+            f.write_str("...")?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, " = {}", default)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints source that parses back to an equivalent program. A bare
+/// `Stmt::Block` has no syntax of its own and comes back as `if true`.
+impl Display for Stmt {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_stmt(f, self, 0)
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_stmts(f, &self.statements, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    fn identity() -> Expr {
+        lambda(&["x"], var("x"))
+    }
+
+    #[test]
+    fn parenthesizes_by_precedence() {
+        let sum = || binary(int(1), "+", int(2));
+        assert_eq!(binary(sum(), "*", int(3)).to_string(), "(1 + 2) * 3");
+        assert_eq!(binary(int(3), "*", sum()).to_string(), "3 * (1 + 2)");
+        assert_eq!(binary(sum(), "+", int(3)).to_string(), "1 + 2 + 3");
+        assert_eq!(binary(int(3), "-", sum()).to_string(), "3 - (1 + 2)");
+        assert_eq!(unary("-", sum()).to_string(), "-(1 + 2)");
+        assert_eq!(
+            binary(unary("-", var("a")), "*", var("b")).to_string(),
+            "-a * b"
+        );
+
+        let either = binary(var("a"), "||", var("b"));
+        assert_eq!(binary(either, "&&", var("c")).to_string(), "(a || b) && c");
+        let range = binary(int(0), "..", var("n"));
+        assert_eq!(binary(range, "==", var("r")).to_string(), "0 .. n == r");
+    }
+
+    #[test]
+    fn drops_redundant_grouping() {
+        let grouped = Expr::Grouped(Box::new(var("a")));
+        assert_eq!(binary(grouped, "+", var("b")).to_string(), "a + b");
+
+        let grouped = Expr::Grouped(Box::new(binary(var("a"), "+", var("b"))));
+        assert_eq!(binary(grouped, "*", var("c")).to_string(), "(a + b) * c");
+    }
+
+    #[test]
+    fn keeps_comparisons_apart_from_chains() {
+        let less = binary(var("a"), "<", var("b"));
+        assert_eq!(
+            binary(less.clone(), "<", var("c")).to_string(),
+            "(a < b) < c"
+        );
+
+        let chain = Expr::Compare {
+            first: Box::new(less),
+            rest: vec![
+                ("<".to_string(), var("c")),
+                ("<=".to_string(), binary(var("d"), "+", int(1))),
+            ],
+        };
+        assert_eq!(chain.to_string(), "(a < b) < c <= d + 1");
+    }
+
+    #[test]
+    fn parenthesizes_callees_and_receivers() {
+        let call = Expr::Call {
+            callee: Box::new(identity()),
+            args: vec![int(1)],
+            kwargs: vec![("scale".to_string(), int(2))],
+        };
+        assert_eq!(call.to_string(), "(|x| x)(1, scale: 2)");
+
+        let method = Expr::MethodCall {
+            object: Box::new(int(-1)),
+            method: "abs".to_string(),
+            args: Vec::new(),
+            kwargs: Vec::new(),
+        };
+        assert_eq!(method.to_string(), "(-1).abs()");
+
+        let spread = Expr::Spread(Box::new(binary(var("a"), "+", var("b"))));
+        assert_eq!(Expr::ArrayLiteral(vec![spread]).to_string(), "[a + b...]");
+        let spread = Expr::Spread(Box::new(identity()));
+        assert_eq!(Expr::ArrayLiteral(vec![spread]).to_string(), "[(|x| x)...]");
+        assert_eq!(Expr::Tuple(vec![int(1)]).to_string(), "(1,)");
+    }
+
+    #[test]
+    fn prints_literals_as_source() {
+        assert_eq!(float(2.0).to_string(), "2.0");
+        assert_eq!(float(1e16).to_string(), "1.0e16");
+        let text = Literal::String("say \"hi\"\n".to_string());
+        assert_eq!(text.to_string(), r#""say \"hi\"\n""#);
+
+        // Values without a literal form print as arithmetic, in parentheses
+        // where precedence needs them
+        assert_eq!(float(f64::INFINITY).to_string(), "1.0 / 0.0");
+        assert_eq!(float(f64::NAN).to_string(), "0.0 / 0.0");
+        let product = binary(float(2.0), "*", float(f64::NEG_INFINITY));
+        assert_eq!(product.to_string(), "2.0 * (-1.0 / 0.0)");
+        let product = binary(int(i64::MIN), "*", int(2));
+        assert_eq!(product.to_string(), "(-9223372036854775807 - 1) * 2");
+        let sum = binary(int(1), "+", int(i64::MIN));
+        assert_eq!(sum.to_string(), "1 + (-9223372036854775807 - 1)");
+    }
+
+    #[test]
+    fn prints_elif_chains() {
+        let call = |name| Stmt::ExprStmt(call(name, Vec::new()));
+        let nested = Stmt::If {
+            condition: var("b"),
+            then_branch: vec![call("g")],
+            else_branch: Some(vec![call("h")]),
+        };
+        let program = Program {
+            statements: vec![Stmt::If {
+                condition: var("a"),
+                then_branch: vec![call("f")],
+                else_branch: Some(vec![nested]),
+            }],
+        };
+        assert_eq!(
+            program.to_string(),
+            "if a {\n    f();\n} elif b {\n    g();\n} else {\n    h();\n}\n"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use super::visit::{Visitor, walk_expr, walk_stmt};
+use super::{Expr, Program, Stmt};
+
+/// Checks that every `break` and `continue` sits inside a loop and that any
+/// label it names belongs to an enclosing loop. Labels may not be reused by
+/// a loop nested inside another loop with the same label.
+pub fn check_labels(program: &Program) -> Result<(), String> {
+    let mut checker = LabelChecker {
+        loops: Vec::new(),
+        error: None,
+    };
+    checker.visit_program(program);
+
+    match checker.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+struct LabelChecker {
+    // Labels of the enclosing loops, innermost last
+    loops: Vec<Option<String>>,
+    error: Option<String>,
+}
+
+impl LabelChecker {
+    fn check_jump(&mut self, keyword: &str, label: &Option<String>) {
+        let error = match label {
+            _ if self.loops.is_empty() => format!("`{}` outside of a loop", keyword),
+            Some(label) if !self.loops.iter().flatten().any(|l| l == label) => {
+                format!("`{} {}` does not name an enclosing loop", keyword, label)
+            }
+            _ => return,
+        };
+        self.error = Some(error);
+    }
+}
+
+impl Visitor for LabelChecker {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match stmt {
+            Stmt::Break(label) => self.check_jump("break", label),
+            Stmt::Continue(label) => self.check_jump("continue", label),
+            Stmt::While { label, .. } | Stmt::Loop { label, .. } | Stmt::For { label, .. } => {
+                if let Some(label) = label
+                    && self.loops.iter().flatten().any(|l| l == label)
+                {
+                    self.error = Some(format!(
+                        "label `{}` is already used by an enclosing loop",
+                        label
+                    ));
+                    return;
+                }
+                self.loops.push(label.clone());
+                walk_stmt(self, stmt);
+                self.loops.pop();
+            }
+            // A nested function cannot jump out of the loops around it
+            Stmt::FuncDecl { .. } => {
+                let outer = std::mem::take(&mut self.loops);
+                walk_stmt(self, stmt);
+                self.loops = outer;
+            }
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    // Neither can a lambda
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Lambda { .. } = expr {
+            let outer = std::mem::take(&mut self.loops);
+            walk_expr(self, expr);
+            self.loops = outer;
+        } else {
+            walk_expr(self, expr);
+        }
+    }
+}
+
+/// True if some statement in `body` leaves the loop that owns it: a bare
+/// `break` outside any nested loop, or a `break` naming `label`.
+pub(super) fn breaks_out_of(body: &[Stmt], label: &Option<String>) -> bool {
+    let mut finder = BreakFinder {
+        label,
+        depth: 0,
+        found: false,
+    };
+    finder.visit_block(body);
+    finder.found
+}
+
+struct BreakFinder<'a> {
+    label: &'a Option<String>,
+    // Loops nested inside the one being checked
+    depth: usize,
+    found: bool,
+}
+
+impl Visitor for BreakFinder<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Break(None) if self.depth == 0 => self.found = true,
+            Stmt::Break(Some(target)) if self.label.as_ref() == Some(target) => self.found = true,
+            Stmt::While { .. } | Stmt::Loop { .. } | Stmt::For { .. } => {
+                self.depth += 1;
+                walk_stmt(self, stmt);
+                self.depth -= 1;
+            }
+            Stmt::FuncDecl { .. } => {}
+            _ => walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if !matches!(expr, Expr::Lambda { .. }) {
+            walk_expr(self, expr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    fn check(statements: Vec<Stmt>) -> Result<(), String> {
+        check_labels(&Program { statements })
+    }
+
+    fn looped(label: Option<&str>, body: Vec<Stmt>) -> Stmt {
+        Stmt::Loop {
+            label: label.map(str::to_string),
+            body,
+        }
+    }
+
+    fn break_to(label: &str) -> Stmt {
+        Stmt::Break(Some(label.to_string()))
+    }
+
+    // `f(|| do { <body>; 1 })`
+    fn call_with_lambda(body: Vec<Stmt>) -> Stmt {
+        Stmt::ExprStmt(call("f", vec![lambda(&[], do_block(body, int(1)))]))
+    }
+
+    #[test]
+    fn accepts_jumps_inside_loops() {
+        let inner = looped(None, vec![break_to("outer"), Stmt::Continue(None)]);
+        assert_eq!(check(vec![looped(Some("outer"), vec![inner])]), Ok(()));
+    }
+
+    #[test]
+    fn rejects_jumps_outside_loops() {
+        assert_eq!(
+            check(vec![Stmt::Break(None)]),
+            Err("`break` outside of a loop".to_string())
+        );
+        assert_eq!(
+            check(vec![looped(None, vec![break_to("outer")])]),
+            Err("`break outer` does not name an enclosing loop".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_reused_labels() {
+        let inner = looped(Some("outer"), Vec::new());
+        assert_eq!(
+            check(vec![looped(Some("outer"), vec![inner])]),
+            Err("label `outer` is already used by an enclosing loop".to_string())
+        );
+    }
+
+    #[test]
+    fn functions_and_lambdas_cannot_jump_out() {
+        let func = func("g", Vec::new(), vec![Stmt::Continue(None)]);
+        assert_eq!(
+            check(vec![looped(None, vec![func])]),
+            Err("`continue` outside of a loop".to_string())
+        );
+
+        let call = call_with_lambda(vec![Stmt::Break(None)]);
+        assert_eq!(
+            check(vec![looped(None, vec![call])]),
+            Err("`break` outside of a loop".to_string())
+        );
+    }
+
+    #[test]
+    fn breaks_in_lambdas_do_not_leave_the_loop() {
+        let call = call_with_lambda(vec![Stmt::Break(None)]);
+        assert!(!breaks_out_of(&[call], &None));
+        assert!(breaks_out_of(&[Stmt::Break(None)], &None));
+    }
+}
@@ -0,0 +1,743 @@
+use std::collections::{HashMap, HashSet};
+
+use super::optimize::{fold_binary, fold_chain, fold_unary, int_error};
+use super::visit::{Visitor, walk_block, walk_expr, walk_stmt};
+use super::{CompClause, Expr, Literal, Param, Program, Stmt};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub lint: &'static str,
+    pub message: String,
+}
+
+/// A single check over a whole program. Lints only report; they never
+/// change the AST.
+pub trait Lint {
+    fn name(&self) -> &'static str;
+    fn check(&mut self, program: &Program) -> Vec<String>;
+}
+
+pub fn default_lints() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(UnreachableCode),
+        Box::new(ConstantCondition),
+        Box::new(ShadowedVariable),
+        Box::new(UnusedFunction),
+        Box::new(UnusedVariable),
+        Box::new(IntegerOverflow),
+    ]
+}
+
+/// Runs every lint in `lints` over `program`, in order.
+pub fn run_lints(program: &Program, lints: &mut [Box<dyn Lint>]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    for lint in lints.iter_mut() {
+        let name = lint.name();
+        warnings.extend(lint.check(program).into_iter().map(|message| Warning {
+            lint: name,
+            message,
+        }));
+    }
+
+    warnings
+}
+
+/// Runs the default lint set.
+pub fn lint(program: &Program) -> Vec<Warning> {
+    run_lints(program, &mut default_lints())
+}
+
+pub struct UnreachableCode;
+
+impl Lint for UnreachableCode {
+    fn name(&self) -> &'static str {
+        "unreachable_code"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Finder(Vec<String>);
+
+        impl Visitor for Finder {
+            fn visit_block(&mut self, stmts: &[Stmt]) {
+                if let Some(pos) = stmts.iter().position(Stmt::diverges)
+                    && pos + 1 < stmts.len()
+                {
+                    let count = stmts.len() - pos - 1;
+                    self.0.push(format!(
+                        "{} statement(s) after `{}` will never run",
+                        count,
+                        jump_keyword(&stmts[pos])
+                    ));
+                }
+                walk_block(self, stmts);
+            }
+        }
+
+        let mut finder = Finder(Vec::new());
+        finder.visit_program(program);
+        finder.0
+    }
+}
+
+// Names a diverging statement; a block is named by the jump inside it
+fn jump_keyword(stmt: &Stmt) -> &'static str {
+    match stmt {
+        Stmt::Raise(_) => "raise",
+        Stmt::Break(_) => "break",
+        Stmt::Continue(_) => "continue",
+        Stmt::Loop { .. } => "loop",
+        Stmt::While { .. } => "while true",
+        Stmt::Block(body) => body
+            .iter()
+            .find(|stmt| stmt.diverges())
+            .map_or("ret", jump_keyword),
+        _ => "ret",
+    }
+}
+
+pub struct ConstantCondition;
+
+impl Lint for ConstantCondition {
+    fn name(&self) -> &'static str {
+        "constant_condition"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Finder(Vec<String>);
+
+        impl Visitor for Finder {
+            fn visit_stmt(&mut self, stmt: &Stmt) {
+                match stmt {
+                    Stmt::If { condition, .. } => {
+                        if let Some(value) = constant_bool(condition) {
+                            self.0.push(format!("`if` condition is always {}", value));
+                        }
+                    }
+                    // `while true` is the usual way to spell an endless loop
+                    Stmt::While { condition, .. } if constant_bool(condition) == Some(false) => {
+                        self.0.push("`while` condition is always false".to_string());
+                    }
+                    _ => {}
+                }
+                walk_stmt(self, stmt);
+            }
+        }
+
+        let mut finder = Finder(Vec::new());
+        finder.visit_program(program);
+        finder.0
+    }
+}
+
+// Defaults are evaluated in the scope around the function, not its own
+fn visit_param_defaults<V: Visitor>(visitor: &mut V, params: &[Param]) {
+    for default in params.iter().filter_map(|param| param.default.as_ref()) {
+        visitor.visit_expr(default);
+    }
+}
+
+fn constant_bool(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Literal(Literal::Bool(value)) => Some(*value),
+        Expr::Grouped(inner) => constant_bool(inner),
+        _ => None,
+    }
+}
+
+/// Shadowing a name from an outer scope is allowed but easy to misread.
+/// Declaring it twice in the same scope is an error from `check_bindings`.
+pub struct ShadowedVariable;
+
+impl Lint for ShadowedVariable {
+    fn name(&self) -> &'static str {
+        "shadowed_variable"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Finder {
+            scopes: Vec<HashSet<String>>,
+            warnings: Vec<String>,
+        }
+
+        impl Finder {
+            fn declare(&mut self, name: &str) {
+                let (current, outer) = self.scopes.split_last_mut().unwrap();
+                if outer.iter().any(|scope| scope.contains(name)) {
+                    self.warnings
+                        .push(format!("`{}` shadows a variable from an outer scope", name));
+                }
+                current.insert(name.to_string());
+            }
+
+            // The loop variables are visible to the filter and the results
+            fn visit_comp(&mut self, clause: &CompClause, results: &[&Expr]) {
+                self.visit_expr(&clause.iter);
+                self.scopes.push(HashSet::new());
+                for var in std::iter::once(&clause.var).chain(&clause.value_var) {
+                    self.declare(var);
+                }
+                if let Some(condition) = &clause.condition {
+                    self.visit_expr(condition);
+                }
+                for result in results {
+                    self.visit_expr(result);
+                }
+                self.scopes.pop();
+            }
+        }
+
+        impl Visitor for Finder {
+            fn visit_block(&mut self, stmts: &[Stmt]) {
+                self.scopes.push(HashSet::new());
+                walk_block(self, stmts);
+                self.scopes.pop();
+            }
+
+            fn visit_stmt(&mut self, stmt: &Stmt) {
+                match stmt {
+                    Stmt::VariableDecl { name, .. } | Stmt::ConstDecl { name, .. } => {
+                        walk_stmt(self, stmt);
+                        self.declare(name);
+                    }
+                    Stmt::TupleDecl { names, .. } => {
+                        walk_stmt(self, stmt);
+                        for name in names {
+                            self.declare(name);
+                        }
+                    }
+                    Stmt::FuncDecl { params, body, .. } => {
+                        visit_param_defaults(self, params);
+                        self.scopes.push(HashSet::new());
+                        for param in params {
+                            self.declare(&param.name);
+                        }
+                        self.visit_block(body);
+                        self.scopes.pop();
+                    }
+                    Stmt::For {
+                        var,
+                        value_var,
+                        iter_expr,
+                        step,
+                        body,
+                        ..
+                    } => {
+                        self.visit_expr(iter_expr);
+                        if let Some(step) = step {
+                            self.visit_expr(step);
+                        }
+                        self.scopes.push(HashSet::new());
+                        for var in std::iter::once(var).chain(value_var) {
+                            self.declare(var);
+                        }
+                        self.visit_block(body);
+                        self.scopes.pop();
+                    }
+                    Stmt::Try {
+                        body,
+                        error_var,
+                        handler,
+                    } => {
+                        self.visit_block(body);
+                        self.scopes.push(HashSet::new());
+                        if let Some(error_var) = error_var {
+                            self.declare(error_var);
+                        }
+                        self.visit_block(handler);
+                        self.scopes.pop();
+                    }
+                    _ => walk_stmt(self, stmt),
+                }
+            }
+
+            fn visit_expr(&mut self, expr: &Expr) {
+                match expr {
+                    Expr::Lambda { params, body } => {
+                        visit_param_defaults(self, params);
+                        self.scopes.push(HashSet::new());
+                        for param in params {
+                            self.declare(&param.name);
+                        }
+                        self.visit_expr(body);
+                        self.scopes.pop();
+                    }
+                    Expr::ListComp { element, clause } => self.visit_comp(clause, &[element]),
+                    Expr::MapComp { key, value, clause } => self.visit_comp(clause, &[key, value]),
+                    // The value can see the block's own declarations
+                    Expr::Do { body, value } => {
+                        self.scopes.push(HashSet::new());
+                        walk_block(self, body);
+                        self.visit_expr(value);
+                        self.scopes.pop();
+                    }
+                    _ => walk_expr(self, expr),
+                }
+            }
+        }
+
+        let mut finder = Finder {
+            scopes: Vec::new(),
+            warnings: Vec::new(),
+        };
+        finder.visit_program(program);
+        finder.warnings
+    }
+}
+
+pub struct UnusedFunction;
+
+impl Lint for UnusedFunction {
+    fn name(&self) -> &'static str {
+        "unused_function"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Calls(HashSet<String>);
+
+        impl Visitor for Calls {
+            fn visit_expr(&mut self, expr: &Expr) {
+                // A function passed as a value counts as used as well
+                if let Expr::FuncCall { name, .. } | Expr::Variable(name) = expr {
+                    self.0.insert(name.clone());
+                }
+                walk_expr(self, expr);
+            }
+        }
+
+        let mut calls = Calls(HashSet::new());
+        calls.visit_program(program);
+
+        // Only free functions; methods can be reached through their type
+        program
+            .statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::FuncDecl { name, .. } if name != "main" && !calls.0.contains(name) => {
+                    Some(format!("function `{}` is never called", name))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Names starting with `_`, and `self`, are treated as intentionally unused.
+pub struct UnusedVariable;
+
+impl Lint for UnusedVariable {
+    fn name(&self) -> &'static str {
+        "unused_variable"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Finder {
+            // name -> (kind, read at least once)
+            scopes: Vec<HashMap<String, (&'static str, bool)>>,
+            warnings: Vec<String>,
+        }
+
+        impl Finder {
+            fn enter(&mut self, names: &[String], kind: &'static str) {
+                self.scopes.push(
+                    names
+                        .iter()
+                        .map(|name| (name.clone(), (kind, false)))
+                        .collect(),
+                );
+            }
+
+            fn exit(&mut self) {
+                let scope = self.scopes.pop().unwrap();
+                let mut unused: Vec<_> = scope
+                    .into_iter()
+                    .filter(|(name, (_, used))| !used && !name.starts_with('_') && name != "self")
+                    .collect();
+                unused.sort_by(|a, b| a.0.cmp(&b.0));
+
+                for (name, (kind, _)) in unused {
+                    self.warnings.push(format!(
+                        "{} `{}` is never read; prefix it with `_` if this is intended",
+                        kind, name
+                    ));
+                }
+            }
+
+            fn read(&mut self, name: &str) {
+                for scope in self.scopes.iter_mut().rev() {
+                    if let Some((_, used)) = scope.get_mut(name) {
+                        *used = true;
+                        return;
+                    }
+                }
+            }
+
+            fn visit_comp(&mut self, clause: &CompClause, results: &[&Expr]) {
+                self.visit_expr(&clause.iter);
+                let vars: Vec<String> = std::iter::once(&clause.var)
+                    .chain(&clause.value_var)
+                    .cloned()
+                    .collect();
+                self.enter(&vars, "loop variable");
+                if let Some(condition) = &clause.condition {
+                    self.visit_expr(condition);
+                }
+                for result in results {
+                    self.visit_expr(result);
+                }
+                self.exit();
+            }
+        }
+
+        impl Visitor for Finder {
+            fn visit_block(&mut self, stmts: &[Stmt]) {
+                self.enter(&[], "variable");
+                walk_block(self, stmts);
+                self.exit();
+            }
+
+            fn visit_stmt(&mut self, stmt: &Stmt) {
+                match stmt {
+                    Stmt::VariableDecl { name, .. } | Stmt::ConstDecl { name, .. } => {
+                        walk_stmt(self, stmt);
+                        let kind = match stmt {
+                            Stmt::ConstDecl { .. } => "constant",
+                            _ => "variable",
+                        };
+                        self.scopes
+                            .last_mut()
+                            .unwrap()
+                            .insert(name.clone(), (kind, false));
+                    }
+                    Stmt::TupleDecl { names, .. } => {
+                        walk_stmt(self, stmt);
+                        let scope = self.scopes.last_mut().unwrap();
+                        for name in names {
+                            scope.insert(name.clone(), ("variable", false));
+                        }
+                    }
+                    Stmt::FuncDecl { params, body, .. } => {
+                        visit_param_defaults(self, params);
+                        let names: Vec<String> =
+                            params.iter().map(|param| param.name.clone()).collect();
+                        self.enter(&names, "parameter");
+                        self.visit_block(body);
+                        self.exit();
+                    }
+                    Stmt::For {
+                        var,
+                        value_var,
+                        iter_expr,
+                        step,
+                        body,
+                        ..
+                    } => {
+                        self.visit_expr(iter_expr);
+                        if let Some(step) = step {
+                            self.visit_expr(step);
+                        }
+                        let vars: Vec<String> =
+                            std::iter::once(var).chain(value_var).cloned().collect();
+                        self.enter(&vars, "loop variable");
+                        self.visit_block(body);
+                        self.exit();
+                    }
+                    Stmt::Try {
+                        body,
+                        error_var,
+                        handler,
+                    } => {
+                        self.visit_block(body);
+                        let names: Vec<String> = error_var.iter().cloned().collect();
+                        self.enter(&names, "error variable");
+                        self.visit_block(handler);
+                        self.exit();
+                    }
+                    // Writing to a plain variable does not count as reading it
+                    Stmt::Assignment { target, value } => {
+                        match target {
+                            Expr::Variable(_) => {}
+                            Expr::Tuple(targets) => {
+                                for target in targets {
+                                    if !matches!(target, Expr::Variable(_)) {
+                                        self.visit_expr(target);
+                                    }
+                                }
+                            }
+                            _ => self.visit_expr(target),
+                        }
+                        self.visit_expr(value);
+                    }
+                    _ => walk_stmt(self, stmt),
+                }
+            }
+
+            fn visit_expr(&mut self, expr: &Expr) {
+                match expr {
+                    Expr::Variable(name) => self.read(name),
+                    Expr::Lambda { params, body } => {
+                        visit_param_defaults(self, params);
+                        let names: Vec<String> =
+                            params.iter().map(|param| param.name.clone()).collect();
+                        self.enter(&names, "parameter");
+                        self.visit_expr(body);
+                        self.exit();
+                        return;
+                    }
+                    Expr::ListComp { element, clause } => {
+                        self.visit_comp(clause, &[element]);
+                        return;
+                    }
+                    Expr::MapComp { key, value, clause } => {
+                        self.visit_comp(clause, &[key, value]);
+                        return;
+                    }
+                    Expr::Do { body, value } => {
+                        self.enter(&[], "variable");
+                        walk_block(self, body);
+                        self.visit_expr(value);
+                        self.exit();
+                        return;
+                    }
+                    _ => {}
+                }
+                walk_expr(self, expr);
+            }
+        }
+
+        let mut finder = Finder {
+            scopes: Vec::new(),
+            warnings: Vec::new(),
+        };
+        finder.visit_program(program);
+        finder.warnings
+    }
+}
+
+/// Integer arithmetic is checked at runtime, so a constant expression that
+/// overflows `i64` or divides by zero is certain to fail when it runs.
+pub struct IntegerOverflow;
+
+impl Lint for IntegerOverflow {
+    fn name(&self) -> &'static str {
+        "integer_overflow"
+    }
+
+    fn check(&mut self, program: &Program) -> Vec<String> {
+        struct Finder(Vec<String>);
+
+        impl Finder {
+            // Folds each constant operation once, bottom-up, and walks into
+            // anything that is not constant. Only the innermost failure is
+            // reported; the operations around it are not constant anymore.
+            fn fold(&mut self, expr: &Expr) -> Option<Literal> {
+                match expr {
+                    Expr::Literal(lit) => Some(lit.clone()),
+                    Expr::Grouped(inner) => self.fold(inner),
+                    Expr::UnaryOp { op, expr } => fold_unary(op, &self.fold(expr)?),
+                    Expr::BinaryOp { left, op, right } => {
+                        let (left, right) = (self.fold(left), self.fold(right));
+                        let (left, right) = (left?, right?);
+                        let folded = fold_binary(&left, op, &right);
+                        if folded.is_none()
+                            && let Some(error) = int_error(&left, op, &right)
+                        {
+                            self.0.push(format!("`{}` {}", expr, error));
+                        }
+                        folded
+                    }
+                    Expr::Compare { first, rest } => {
+                        let first = self.fold(first);
+                        let operands: Vec<_> =
+                            rest.iter().map(|(_, operand)| self.fold(operand)).collect();
+                        let mut operands = operands.into_iter();
+                        fold_chain(first?, rest, |_| operands.next()?)
+                    }
+                    _ => {
+                        walk_expr(self, expr);
+                        None
+                    }
+                }
+            }
+        }
+
+        impl Visitor for Finder {
+            fn visit_expr(&mut self, expr: &Expr) {
+                self.fold(expr);
+            }
+        }
+
+        let mut finder = Finder(Vec::new());
+        finder.visit_program(program);
+        finder.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_util::*;
+    use super::*;
+
+    fn check(mut lint: impl Lint, statements: Vec<Stmt>) -> Vec<String> {
+        lint.check(&Program { statements })
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let body = vec![
+            Stmt::Return(int(1)),
+            Stmt::ExprStmt(call("f", vec![])),
+            Stmt::ExprStmt(call("g", vec![])),
+        ];
+        assert_eq!(
+            check(UnreachableCode, vec![func("main", Vec::new(), body)]),
+            ["2 statement(s) after `ret` will never run"]
+        );
+
+        // A loop that can be left is not a jump
+        let looped = Stmt::Loop {
+            label: None,
+            body: vec![Stmt::Break(None)],
+        };
+        assert!(
+            check(
+                UnreachableCode,
+                vec![looped, Stmt::ExprStmt(call("f", vec![]))]
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn constant_condition() {
+        let if_ = |condition| Stmt::If {
+            condition,
+            then_branch: Vec::new(),
+            else_branch: None,
+        };
+        let while_ = |value| Stmt::While {
+            label: None,
+            condition: boolean(value),
+            body: vec![Stmt::Break(None)],
+        };
+        let statements = vec![
+            if_(boolean(true)),
+            if_(var("x")),
+            while_(false),
+            while_(true),
+        ];
+        assert_eq!(
+            check(ConstantCondition, statements),
+            [
+                "`if` condition is always true",
+                "`while` condition is always false"
+            ]
+        );
+    }
+
+    #[test]
+    fn shadowed_variable() {
+        let warning = "`i` shadows a variable from an outer scope";
+        let shadowing = [
+            Stmt::Block(vec![let_("i", int(1))]),
+            for_("i", Vec::new()),
+            func("f", vec![param("i")], Vec::new()),
+        ];
+        for stmt in shadowing {
+            assert_eq!(
+                check(ShadowedVariable, vec![let_("i", int(0)), stmt]),
+                [warning]
+            );
+        }
+
+        // Redeclaring in the same scope, or in a sibling one, is not shadowing
+        let statements = vec![
+            for_("i", Vec::new()),
+            for_("i", Vec::new()),
+            let_("j", int(0)),
+            let_("j", int(1)),
+        ];
+        assert!(check(ShadowedVariable, statements).is_empty());
+    }
+
+    #[test]
+    fn unused_function() {
+        let statements = vec![
+            func(
+                "main",
+                Vec::new(),
+                vec![Stmt::ExprStmt(call("used", vec![]))],
+            ),
+            func("used", Vec::new(), vec![let_("f", var("passed"))]),
+            func("passed", Vec::new(), Vec::new()),
+            func("unused", Vec::new(), Vec::new()),
+        ];
+        assert_eq!(
+            check(UnusedFunction, statements),
+            ["function `unused` is never called"]
+        );
+    }
+
+    #[test]
+    fn unused_variable() {
+        let assign = Stmt::Assignment {
+            target: var("written"),
+            value: int(1),
+        };
+        let body = vec![
+            let_("read", int(0)),
+            let_("_ignored", int(0)),
+            let_("written", int(0)),
+            assign,
+            for_("item", vec![Stmt::ExprStmt(call("f", vec![var("read")]))]),
+        ];
+        assert_eq!(
+            check(
+                UnusedVariable,
+                vec![func("main", vec![param("arg"), param("self")], body)]
+            ),
+            [
+                "loop variable `item` is never read; prefix it with `_` if this is intended",
+                "variable `written` is never read; prefix it with `_` if this is intended",
+                "parameter `arg` is never read; prefix it with `_` if this is intended",
+            ]
+        );
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let overflow = binary(int(i64::MAX), "+", int(1));
+        let statements = vec![
+            let_("a", binary(int(1), "/", int(0))),
+            // Only the innermost failure is reported
+            let_("b", binary(overflow, "*", int(2))),
+            let_("c", binary(var("x"), "%", int(0))),
+            let_("d", binary(int(6), "/", int(3))),
+            // Operands of a comparison are checked even after it fails
+            let_(
+                "e",
+                chain(
+                    int(2),
+                    vec![("<", int(1)), ("<", binary(int(1), "%", int(0)))],
+                ),
+            ),
+        ];
+        assert_eq!(
+            check(IntegerOverflow, statements),
+            [
+                "`1 / 0` divides by zero",
+                "`9223372036854775807 + 1` overflows i64",
+                "`1 % 0` divides by zero",
+            ]
+        );
+    }
+
+    #[test]
+    fn huge_repeats_are_not_built() {
+        let pad = binary(string("ab"), "*", int(1_000_000_000_000));
+        let program = Program {
+            statements: vec![let_("_pad", pad)],
+        };
+        assert_eq!(lint(&program), []);
+    }
+}
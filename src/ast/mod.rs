@@ -1,4 +1,24 @@
+mod args;
+mod bindings;
+mod consts;
+mod display;
+mod labels;
+pub mod lint;
+mod optimize;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod visit;
+
+pub use args::check_args;
+pub use bindings::check_bindings;
+pub use consts::eval_consts;
+pub use labels::check_labels;
+pub use optimize::optimize;
+
+use visit::{Visitor, walk_expr, walk_stmt};
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     Literal(Literal),
     Variable(String),
@@ -6,14 +26,36 @@ pub enum Expr {
         op: String,
         expr: Box<Expr>,
     },
+    Ref {
+        mutable: bool,
+        expr: Box<Expr>,
+    },
     BinaryOp {
         left: Box<Expr>,
         op: String,
         right: Box<Expr>,
     },
+    // `a < b <= c` holds when every adjacent pair does; each operand is
+    // evaluated once, left to right, stopping at the first pair that fails
+    Compare {
+        first: Box<Expr>,
+        rest: Vec<(String, Expr)>,
+    },
     FuncCall {
         name: String,
         args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>, // `f(name: value)`
+    },
+    MethodCall {
+        object: Box<Expr>, // an instance, or a type name for associated functions
+        method: String,
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
+    },
+    Call {
+        callee: Box<Expr>, // any other expression: `make(1)(2)`, `fs[0](x)`
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
     },
     FieldAccess {
         object: Box<Expr>,
@@ -24,11 +66,48 @@ pub enum Expr {
         index: Box<Expr>,
     },
     ArrayLiteral(Vec<Expr>),
-    MapLiteral(Vec<(Expr, Expr)>),
+    MapLiteral(Vec<MapEntry>),
+    Tuple(Vec<Expr>),
     Grouped(Box<Expr>),
+    Spread(Box<Expr>), // `xs...`
+    Lambda {
+        params: Vec<Param>,
+        body: Box<Expr>, // `|x| x + 1`
+    },
+    Do {
+        body: Vec<Stmt>,
+        value: Box<Expr>, // what the block evaluates to
+    },
+    ListComp {
+        element: Box<Expr>,
+        clause: CompClause,
+    },
+    MapComp {
+        key: Box<Expr>,
+        value: Box<Expr>,
+        clause: CompClause,
+    },
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MapEntry {
+    Pair(Expr, Expr),
+    Spread(Expr), // `{base..., "extra": 1}`; later keys win
+}
+
+/// The `for var in iter if condition` part shared by comprehensions.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompClause {
+    pub var: String,
+    pub value_var: Option<String>, // `for k, v in map`, as in for loops
+    pub iter: Box<Expr>,
+    pub condition: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     Int(i64),
     Float(f64),
@@ -38,21 +117,31 @@ pub enum Literal {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stmt {
     VariableDecl {
         name: String,
+        ty: Option<String>,
         expr: Option<Expr>,
     },
+    TupleDecl {
+        names: Vec<String>, // `let (a, b) = expr`
+        expr: Expr,
+    },
     ConstDecl {
         name: String,
+        ty: String,
         expr: Expr,
     },
     FuncDecl {
+        doc: Option<String>, // from `##` lines above the declaration
         name: String,
-        params: Vec<String>,
+        params: Vec<Param>,
+        return_type: Option<String>,
         body: Vec<Stmt>,
     },
     StructDecl {
+        doc: Option<String>,
         name: String,
         fields: Vec<(String, String)>, // field name + type
     },
@@ -61,23 +150,36 @@ pub enum Stmt {
         methods: Vec<Stmt>, // Expect FuncDecls
     },
     Return(Expr),
+    Raise(Expr), // unwinds to the nearest `catch`
+    Yield(Expr),
+    Break(Option<String>), // optional loop label
+    Continue(Option<String>),
     Assignment {
         target: Expr,
         value: Expr,
     },
     ExprStmt(Expr),
+    Block(Vec<Stmt>),
     If {
         condition: Expr,
         then_branch: Vec<Stmt>,
         else_branch: Option<Vec<Stmt>>,
     },
     While {
+        label: Option<String>,
         condition: Expr,
         body: Vec<Stmt>,
     },
+    Loop {
+        label: Option<String>,
+        body: Vec<Stmt>,
+    },
     For {
+        label: Option<String>,
         var: String,
+        value_var: Option<String>, // `for k, v in map`
         iter_expr: Expr,
+        step: Option<Expr>, // `for i in 0..10 step 2`
         body: Vec<Stmt>,
     },
     Switch {
@@ -85,9 +187,113 @@ pub enum Stmt {
         cases: Vec<(Expr, Vec<Stmt>)>,
         default: Option<Vec<Stmt>>,
     },
+    Try {
+        body: Vec<Stmt>,
+        error_var: Option<String>, // bound to the caught error inside `handler`
+        handler: Vec<Stmt>,
+    },
+}
+
+impl Stmt {
+    /// True when control never reaches the statement after this one in the
+    /// same block: `ret`, `raise`, `break`, `continue`, a `loop` or
+    /// `while true` that nothing breaks out of, and a block that contains
+    /// one of those.
+    pub fn diverges(&self) -> bool {
+        match self {
+            Stmt::Return(_) | Stmt::Raise(_) | Stmt::Break(_) | Stmt::Continue(_) => true,
+            Stmt::Block(body) => body.iter().any(Stmt::diverges),
+            Stmt::Loop { label, body }
+            | Stmt::While {
+                label,
+                condition: Expr::Literal(Literal::Bool(true)),
+                body,
+            } => !labels::breaks_out_of(body, label),
+            _ => false,
+        }
+    }
+
+    /// True for a function whose body yields, outside any nested function
+    /// or lambda. Calling it produces a generator instead of running the
+    /// body.
+    pub fn is_generator(&self) -> bool {
+        struct Finder(bool);
+
+        impl Visitor for Finder {
+            fn visit_stmt(&mut self, stmt: &Stmt) {
+                match stmt {
+                    Stmt::Yield(_) => self.0 = true,
+                    Stmt::FuncDecl { .. } => {}
+                    _ => walk_stmt(self, stmt),
+                }
+            }
+
+            fn visit_expr(&mut self, expr: &Expr) {
+                if !matches!(expr, Expr::Lambda { .. }) {
+                    walk_expr(self, expr);
+                }
+            }
+        }
+
+        match self {
+            Stmt::FuncDecl { body, .. } => {
+                let mut finder = Finder(false);
+                finder.visit_block(body);
+                finder.0
+            }
+            _ => false,
+        }
+    }
+
+    /// True for a function declared inside an `impl` block whose first
+    /// parameter is `self`; other functions there are associated functions
+    /// called as `Type.name(...)`.
+    pub fn is_instance_method(&self) -> bool {
+        match self {
+            Stmt::FuncDecl { params, .. } => params.first().is_some_and(|p| p.name == "self"),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Param {
+    pub name: String,
+    pub ty: Option<String>, // required on func params, optional on lambda params
+    pub default: Option<Expr>,
+    pub variadic: bool, // `nums: i64...` collects the remaining arguments
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub statements: Vec<Stmt>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::*;
+    use super::*;
+
+    fn generator(body: Vec<Stmt>) -> Stmt {
+        func("gen", Vec::new(), body)
+    }
+
+    #[test]
+    fn blocks_diverge_with_their_statements() {
+        assert!(Stmt::Block(vec![let_("x", int(1)), Stmt::Return(var("x"))]).diverges());
+        assert!(!Stmt::Block(vec![let_("x", int(1))]).diverges());
+    }
+
+    #[test]
+    fn generators_yield_in_their_own_body() {
+        let yield_one = || Stmt::Yield(int(1));
+        assert!(generator(vec![yield_one()]).is_generator());
+        assert!(!generator(vec![generator(vec![yield_one()])]).is_generator());
+
+        // `let f = || do { yield 1; 1 }`
+        let lambda = lambda(&[], do_block(vec![yield_one()], int(1)));
+        assert!(!generator(vec![let_("f", lambda)]).is_generator());
+    }
+}
@@ -0,0 +1,370 @@
+use super::visit::{VisitorMut, walk_block_mut, walk_expr_mut};
+use super::{Expr, Literal, Program, Stmt};
+
+/// Folds literal expressions, drops branches with a constant condition and
+/// removes statements that can never run, such as those after a `ret` in the
+/// same block.
+pub fn optimize(mut program: Program) -> Program {
+    Optimizer.visit_program(&mut program);
+    program
+}
+
+struct Optimizer;
+
+impl VisitorMut for Optimizer {
+    fn visit_block(&mut self, stmts: &mut Vec<Stmt>) {
+        // Anything after a jump in the same block can never run
+        if let Some(pos) = stmts.iter().position(Stmt::diverges) {
+            stmts.truncate(pos + 1);
+        }
+
+        walk_block_mut(self, stmts);
+
+        *stmts = std::mem::take(stmts)
+            .into_iter()
+            .filter_map(simplify_stmt)
+            .collect();
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+
+        if repeats_too_long(expr) {
+            return;
+        }
+        if let Some(folded) = fold_expr(expr) {
+            *expr = Expr::Literal(folded);
+        }
+    }
+}
+
+// Very long repeated strings are cheaper to build at runtime than to store in
+// the program. Constants are still evaluated in full.
+fn repeats_too_long(expr: &Expr) -> bool {
+    const MAX_FOLDED_LEN: u64 = 4096;
+
+    let Expr::BinaryOp { left, op, right } = expr else {
+        return false;
+    };
+    match (left.as_ref(), right.as_ref()) {
+        (Expr::Literal(Literal::String(s)), Expr::Literal(Literal::Int(n)))
+        | (Expr::Literal(Literal::Int(n)), Expr::Literal(Literal::String(s)))
+            if op == "*" =>
+        {
+            (s.len() as u64).saturating_mul(n.unsigned_abs()) > MAX_FOLDED_LEN
+        }
+        _ => false,
+    }
+}
+
+// Runs after the statement's children were optimized, so conditions are
+// already folded when they are constant.
+fn simplify_stmt(stmt: Stmt) -> Option<Stmt> {
+    match stmt {
+        Stmt::If {
+            condition: Expr::Literal(Literal::Bool(taken)),
+            then_branch,
+            else_branch,
+        } => {
+            // Keep the taken branch as a block so its scope is preserved
+            if taken {
+                Some(Stmt::Block(then_branch))
+            } else {
+                else_branch.map(Stmt::Block)
+            }
+        }
+        Stmt::While {
+            condition: Expr::Literal(Literal::Bool(false)),
+            ..
+        } => None,
+        stmt => Some(stmt),
+    }
+}
+
+fn fold_expr(expr: &Expr) -> Option<Literal> {
+    match expr {
+        Expr::Grouped(inner) => match inner.as_ref() {
+            Expr::Literal(lit) => Some(lit.clone()),
+            _ => None,
+        },
+        Expr::UnaryOp { op, expr } => match expr.as_ref() {
+            Expr::Literal(lit) => fold_unary(op, lit),
+            _ => None,
+        },
+        Expr::BinaryOp { left, op, right } => match (left.as_ref(), right.as_ref()) {
+            // Short-circuiting operators never evaluate the right side here
+            (Expr::Literal(Literal::Bool(false)), _) if op == "&&" => Some(Literal::Bool(false)),
+            (Expr::Literal(Literal::Bool(true)), _) if op == "||" => Some(Literal::Bool(true)),
+            (Expr::Literal(l), Expr::Literal(r)) => fold_binary(l, op, r),
+            _ => None,
+        },
+        Expr::Compare { first, rest } => match first.as_ref() {
+            Expr::Literal(first) => fold_chain(first.clone(), rest, |operand| match operand {
+                Expr::Literal(lit) => Some(lit.clone()),
+                _ => None,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub(super) fn fold_unary(op: &str, lit: &Literal) -> Option<Literal> {
+    match (op, lit) {
+        ("-", Literal::Int(n)) => n.checked_neg().map(Literal::Int),
+        ("-", Literal::Float(n)) => Some(Literal::Float(-n)),
+        ("!", Literal::Bool(b)) => Some(Literal::Bool(!b)),
+        _ => None,
+    }
+}
+
+// Overflowing or dividing-by-zero integer operations are left for the
+// runtime so the error is reported where the program actually runs.
+pub(super) fn fold_binary(left: &Literal, op: &str, right: &Literal) -> Option<Literal> {
+    use Literal::*;
+
+    let folded = match (left, right) {
+        (Int(a), Int(b)) => match op {
+            "+" => Int(a.checked_add(*b)?),
+            "-" => Int(a.checked_sub(*b)?),
+            "*" => Int(a.checked_mul(*b)?),
+            "/" => Int(a.checked_div(*b)?),
+            "%" => Int(a.checked_rem(*b)?),
+            _ => Bool(compare(op, a, b)?),
+        },
+        (Float(a), Float(b)) => match op {
+            "+" => Float(a + b),
+            "-" => Float(a - b),
+            "*" => Float(a * b),
+            "/" => Float(a / b),
+            "%" => Float(a % b),
+            _ => Bool(compare(op, a, b)?),
+        },
+        (String(a), String(b)) => match op {
+            "+" => String(format!("{}{}", a, b)),
+            _ => Bool(compare(op, a, b)?),
+        },
+        (String(s), Int(n)) | (Int(n), String(s)) if op == "*" => String(repeat(s, *n)?),
+        (Bool(a), Bool(b)) => match op {
+            "&&" => Bool(*a && *b),
+            "||" => Bool(*a || *b),
+            "==" => Bool(a == b),
+            "!=" => Bool(a != b),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    // There is no literal for infinity or NaN, so keep the expression
+    if let Float(n) = folded
+        && !n.is_finite()
+    {
+        return None;
+    }
+    Some(folded)
+}
+
+// Why `fold_binary` refused an integer operation, for passes that report it
+pub(super) fn int_error(left: &Literal, op: &str, right: &Literal) -> Option<&'static str> {
+    let (Literal::Int(_), Literal::Int(divisor)) = (left, right) else {
+        return None;
+    };
+    match op {
+        "/" | "%" if *divisor == 0 => Some("divides by zero"),
+        "+" | "-" | "*" | "/" | "%" if fold_binary(left, op, right).is_none() => {
+            Some("overflows i64")
+        }
+        _ => None,
+    }
+}
+
+// Operands after the first failing pair are never evaluated, so they don't
+// need to be constant.
+pub(super) fn fold_chain(
+    first: Literal,
+    rest: &[(String, Expr)],
+    mut eval: impl FnMut(&Expr) -> Option<Literal>,
+) -> Option<Literal> {
+    let mut left = first;
+    for (op, operand) in rest {
+        let right = eval(operand)?;
+        match fold_binary(&left, op, &right)? {
+            Literal::Bool(true) => left = right,
+            Literal::Bool(false) => return Some(Literal::Bool(false)),
+            _ => return None,
+        }
+    }
+    Some(Literal::Bool(true))
+}
+
+// Longest string a fold will build. Every caller folds source it was handed,
+// so the length is checked before anything is allocated.
+const MAX_REPEAT_LEN: usize = 1 << 20;
+
+// True for a string repeat that `fold_binary` refuses because the result
+// would be longer than `MAX_REPEAT_LEN`.
+pub(super) fn too_long(left: &Literal, op: &str, right: &Literal) -> bool {
+    match (left, right) {
+        (Literal::String(s), Literal::Int(n)) | (Literal::Int(n), Literal::String(s))
+            if op == "*" =>
+        {
+            usize::try_from(*n).is_ok_and(|count| {
+                s.len()
+                    .checked_mul(count)
+                    .is_none_or(|len| len > MAX_REPEAT_LEN)
+            })
+        }
+        _ => false,
+    }
+}
+
+// `"ab" * 3`. Negative counts are a runtime error.
+fn repeat(s: &str, count: i64) -> Option<String> {
+    let count = usize::try_from(count).ok()?;
+    if s.len().checked_mul(count)? > MAX_REPEAT_LEN {
+        return None;
+    }
+    Some(s.repeat(count))
+}
+
+fn compare<T: PartialOrd>(op: &str, a: &T, b: &T) -> Option<bool> {
+    match op {
+        "==" => Some(a == b),
+        "!=" => Some(a != b),
+        "<" => Some(a < b),
+        ">" => Some(a > b),
+        "<=" => Some(a <= b),
+        ">=" => Some(a >= b),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lint::{Lint, UnreachableCode};
+    use super::super::test_util::*;
+    use super::*;
+
+    // The optimized expression, printed back as source
+    fn fold(expr: Expr) -> String {
+        let program = optimize(Program {
+            statements: vec![Stmt::ExprStmt(expr)],
+        });
+        match &program.statements[..] {
+            [Stmt::ExprStmt(expr)] => expr.to_string(),
+            other => panic!("unexpected statements: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn folds_literal_arithmetic() {
+        let expr = binary(int(1), "+", binary(int(2), "*", int(3)));
+        assert_eq!(fold(expr), "7");
+        let expr = Expr::Grouped(Box::new(binary(float(1.5), "*", float(2.0))));
+        assert_eq!(fold(expr), "3.0");
+        assert_eq!(fold(binary(string("ab"), "*", int(3))), "\"ababab\"");
+        assert_eq!(fold(binary(int(7), "%", int(4))), "3");
+        assert_eq!(fold(binary(int(2), "<", int(3))), "true");
+    }
+
+    #[test]
+    fn leaves_failing_operations_for_the_runtime() {
+        assert_eq!(fold(binary(int(1), "/", int(0))), "1 / 0");
+        assert_eq!(fold(binary(int(1), "%", int(0))), "1 % 0");
+        assert_eq!(
+            fold(binary(int(i64::MAX), "+", int(1))),
+            "9223372036854775807 + 1"
+        );
+        assert_eq!(
+            fold(binary(int(i64::MIN), "/", int(-1))),
+            "(-9223372036854775807 - 1) / -1"
+        );
+        assert_eq!(
+            fold(unary("-", int(i64::MIN))),
+            "-(-9223372036854775807 - 1)"
+        );
+        assert_eq!(fold(binary(string("ab"), "*", int(-1))), "\"ab\" * -1");
+    }
+
+    #[test]
+    fn keeps_non_finite_floats_as_expressions() {
+        assert_eq!(fold(binary(float(1.0), "/", float(0.0))), "1.0 / 0.0");
+        assert_eq!(fold(binary(float(0.0), "/", float(0.0))), "0.0 / 0.0");
+    }
+
+    #[test]
+    fn caps_repeated_strings() {
+        let expr = binary(string("-"), "*", int(5000));
+        assert_eq!(fold(expr), "\"-\" * 5000");
+        let expr = binary(int(i64::MAX), "*", string("ab"));
+        assert_eq!(fold(expr), "9223372036854775807 * \"ab\"");
+    }
+
+    #[test]
+    fn short_circuits() {
+        assert_eq!(
+            fold(binary(boolean(false), "&&", call("f", vec![]))),
+            "false"
+        );
+        assert_eq!(fold(binary(boolean(true), "||", call("f", vec![]))), "true");
+        assert_eq!(
+            fold(binary(call("f", vec![]), "&&", boolean(false))),
+            "f() && false"
+        );
+    }
+
+    #[test]
+    fn folds_comparison_chains() {
+        let expr = chain(int(1), vec![("<", int(2)), ("<=", int(2))]);
+        assert_eq!(fold(expr), "true");
+        // `f()` is never called once `2 < 1` fails
+        let expr = chain(int(2), vec![("<", int(1)), ("<", call("f", vec![]))]);
+        assert_eq!(fold(expr), "false");
+        let expr = chain(int(1), vec![("<", int(2)), ("<", call("f", vec![]))]);
+        assert_eq!(fold(expr), "1 < 2 < f()");
+    }
+
+    #[test]
+    fn simplifies_statements() {
+        let then_branch = vec![Stmt::ExprStmt(call("a", vec![]))];
+        let else_branch = Some(vec![Stmt::ExprStmt(call("b", vec![]))]);
+        let program = optimize(Program {
+            statements: vec![
+                Stmt::If {
+                    condition: binary(int(1), "<", int(2)),
+                    then_branch,
+                    else_branch,
+                },
+                Stmt::While {
+                    label: None,
+                    condition: boolean(false),
+                    body: vec![Stmt::ExprStmt(call("c", vec![]))],
+                },
+                Stmt::Return(int(0)),
+                Stmt::ExprStmt(call("d", vec![])),
+            ],
+        });
+        // The taken branch stays a block, which prints as `if true`
+        assert!(matches!(&program.statements[0], Stmt::Block(body) if body.len() == 1));
+        assert_eq!(program.to_string(), "if true {\n    a();\n}\nret 0;\n");
+    }
+
+    #[test]
+    fn folded_branches_keep_diverging() {
+        let program = optimize(Program {
+            statements: vec![
+                Stmt::If {
+                    condition: boolean(true),
+                    then_branch: vec![Stmt::Return(int(1))],
+                    else_branch: None,
+                },
+                Stmt::ExprStmt(call("f", vec![])),
+            ],
+        });
+        assert!(program.statements[0].diverges());
+        assert_eq!(
+            UnreachableCode.check(&program),
+            ["1 statement(s) after `ret` will never run"]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! AST builders shared by the unit tests of the passes in this module.
+
+use super::{Expr, Literal, Param, Stmt};
+
+pub(crate) fn int(n: i64) -> Expr {
+    Expr::Literal(Literal::Int(n))
+}
+
+pub(crate) fn float(n: f64) -> Expr {
+    Expr::Literal(Literal::Float(n))
+}
+
+pub(crate) fn string(s: &str) -> Expr {
+    Expr::Literal(Literal::String(s.to_string()))
+}
+
+pub(crate) fn boolean(b: bool) -> Expr {
+    Expr::Literal(Literal::Bool(b))
+}
+
+pub(crate) fn var(name: &str) -> Expr {
+    Expr::Variable(name.to_string())
+}
+
+pub(crate) fn unary(op: &str, expr: Expr) -> Expr {
+    Expr::UnaryOp {
+        op: op.to_string(),
+        expr: Box::new(expr),
+    }
+}
+
+pub(crate) fn binary(left: Expr, op: &str, right: Expr) -> Expr {
+    Expr::BinaryOp {
+        left: Box::new(left),
+        op: op.to_string(),
+        right: Box::new(right),
+    }
+}
+
+// `first op operand op operand ...`
+pub(crate) fn chain(first: Expr, rest: Vec<(&str, Expr)>) -> Expr {
+    Expr::Compare {
+        first: Box::new(first),
+        rest: rest
+            .into_iter()
+            .map(|(op, operand)| (op.to_string(), operand))
+            .collect(),
+    }
+}
+
+pub(crate) fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::FuncCall {
+        name: name.to_string(),
+        args,
+        kwargs: Vec::new(),
+    }
+}
+
+// `|a, b| body`, without type annotations
+pub(crate) fn lambda(params: &[&str], body: Expr) -> Expr {
+    Expr::Lambda {
+        params: params
+            .iter()
+            .map(|name| Param {
+                ty: None,
+                ..param(name)
+            })
+            .collect(),
+        body: Box::new(body),
+    }
+}
+
+pub(crate) fn do_block(body: Vec<Stmt>, value: Expr) -> Expr {
+    Expr::Do {
+        body,
+        value: Box::new(value),
+    }
+}
+
+// `name: i64`
+pub(crate) fn param(name: &str) -> Param {
+    Param {
+        name: name.to_string(),
+        ty: Some("i64".to_string()),
+        default: None,
+        variadic: false,
+    }
+}
+
+pub(crate) fn func(name: &str, params: Vec<Param>, body: Vec<Stmt>) -> Stmt {
+    Stmt::FuncDecl {
+        doc: None,
+        name: name.to_string(),
+        params,
+        return_type: None,
+        body,
+    }
+}
+
+pub(crate) fn let_(name: &str, expr: Expr) -> Stmt {
+    Stmt::VariableDecl {
+        name: name.to_string(),
+        ty: None,
+        expr: Some(expr),
+    }
+}
+
+pub(crate) fn konst(name: &str, expr: Expr) -> Stmt {
+    Stmt::ConstDecl {
+        name: name.to_string(),
+        ty: "i64".to_string(),
+        expr,
+    }
+}
+
+// `for var in xs { body }`
+pub(crate) fn for_(var: &str, body: Vec<Stmt>) -> Stmt {
+    Stmt::For {
+        label: None,
+        var: var.to_string(),
+        value_var: None,
+        iter_expr: Expr::Variable("xs".to_string()),
+        step: None,
+        body,
+    }
+}
@@ -0,0 +1,410 @@
+use super::{CompClause, Expr, Literal, MapEntry, Program, Stmt};
+
+/// Read-only traversal over the AST. Every method defaults to the matching
+/// `walk_*` function, so an implementation only overrides the nodes it cares
+/// about and calls `walk_*` itself when it still wants the children visited.
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_block(&mut self, stmts: &[Stmt]) {
+        walk_block(self, stmts);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_literal(&mut self, _literal: &Literal) {}
+}
+
+/// Mutable counterpart of [`Visitor`]. `visit_block` receives the owning
+/// `Vec` so passes can insert, replace or remove statements.
+pub trait VisitorMut {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_block(&mut self, stmts: &mut Vec<Stmt>) {
+        walk_block_mut(self, stmts);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_literal(&mut self, _literal: &mut Literal) {}
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    visitor.visit_block(&program.statements);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, stmts: &[Stmt]) {
+    for stmt in stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::VariableDecl { expr, .. } => {
+            if let Some(expr) = expr {
+                visitor.visit_expr(expr);
+            }
+        }
+        Stmt::TupleDecl { expr, .. } | Stmt::ConstDecl { expr, .. } => visitor.visit_expr(expr),
+        Stmt::FuncDecl { params, body, .. } => {
+            for param in params {
+                if let Some(default) = &param.default {
+                    visitor.visit_expr(default);
+                }
+            }
+            visitor.visit_block(body);
+        }
+        Stmt::StructDecl { .. } => {}
+        Stmt::ImplDecl { methods, .. } => visitor.visit_block(methods),
+        Stmt::Return(expr) | Stmt::Raise(expr) | Stmt::Yield(expr) => visitor.visit_expr(expr),
+        Stmt::Break(_) | Stmt::Continue(_) => {}
+        Stmt::Assignment { target, value } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        Stmt::ExprStmt(expr) => visitor.visit_expr(expr),
+        Stmt::Block(body) => visitor.visit_block(body),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_block(else_branch);
+            }
+        }
+        Stmt::While {
+            condition, body, ..
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        Stmt::Loop { body, .. } => visitor.visit_block(body),
+        Stmt::For {
+            iter_expr,
+            step,
+            body,
+            ..
+        } => {
+            visitor.visit_expr(iter_expr);
+            if let Some(step) = step {
+                visitor.visit_expr(step);
+            }
+            visitor.visit_block(body);
+        }
+        Stmt::Switch {
+            expr,
+            cases,
+            default,
+        } => {
+            visitor.visit_expr(expr);
+            for (value, body) in cases {
+                visitor.visit_expr(value);
+                visitor.visit_block(body);
+            }
+            if let Some(default) = default {
+                visitor.visit_block(default);
+            }
+        }
+        Stmt::Try { body, handler, .. } => {
+            visitor.visit_block(body);
+            visitor.visit_block(handler);
+        }
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Literal(literal) => visitor.visit_literal(literal),
+        Expr::Variable(_) => {}
+        Expr::UnaryOp { expr, .. } | Expr::Ref { expr, .. } => visitor.visit_expr(expr),
+        Expr::BinaryOp { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Compare { first, rest } => {
+            visitor.visit_expr(first);
+            for (_, operand) in rest {
+                visitor.visit_expr(operand);
+            }
+        }
+        Expr::FuncCall { args, kwargs, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::MethodCall {
+            object: callee,
+            args,
+            kwargs,
+            ..
+        }
+        | Expr::Call {
+            callee,
+            args,
+            kwargs,
+        } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::FieldAccess { object, .. } => visitor.visit_expr(object),
+        Expr::ArrayAccess { object, index } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        }
+        Expr::ArrayLiteral(elements) | Expr::Tuple(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        }
+        Expr::MapLiteral(entries) => {
+            for entry in entries {
+                match entry {
+                    MapEntry::Pair(key, value) => {
+                        visitor.visit_expr(key);
+                        visitor.visit_expr(value);
+                    }
+                    MapEntry::Spread(base) => visitor.visit_expr(base),
+                }
+            }
+        }
+        Expr::Grouped(inner) | Expr::Spread(inner) => visitor.visit_expr(inner),
+        Expr::Lambda { params, body } => {
+            for param in params {
+                if let Some(default) = &param.default {
+                    visitor.visit_expr(default);
+                }
+            }
+            visitor.visit_expr(body);
+        }
+        Expr::Do { body, value } => {
+            visitor.visit_block(body);
+            visitor.visit_expr(value);
+        }
+        Expr::ListComp { element, clause } => {
+            walk_comp_clause(visitor, clause);
+            visitor.visit_expr(element);
+        }
+        Expr::MapComp { key, value, clause } => {
+            walk_comp_clause(visitor, clause);
+            visitor.visit_expr(key);
+            visitor.visit_expr(value);
+        }
+    }
+}
+
+fn walk_comp_clause<V: Visitor + ?Sized>(visitor: &mut V, clause: &CompClause) {
+    visitor.visit_expr(&clause.iter);
+    if let Some(condition) = &clause.condition {
+        visitor.visit_expr(condition);
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    visitor.visit_block(&mut program.statements);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmts: &mut Vec<Stmt>) {
+    for stmt in stmts {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::VariableDecl { expr, .. } => {
+            if let Some(expr) = expr {
+                visitor.visit_expr(expr);
+            }
+        }
+        Stmt::TupleDecl { expr, .. } | Stmt::ConstDecl { expr, .. } => visitor.visit_expr(expr),
+        Stmt::FuncDecl { params, body, .. } => {
+            for param in params {
+                if let Some(default) = &mut param.default {
+                    visitor.visit_expr(default);
+                }
+            }
+            visitor.visit_block(body);
+        }
+        Stmt::StructDecl { .. } => {}
+        Stmt::ImplDecl { methods, .. } => visitor.visit_block(methods),
+        Stmt::Return(expr) | Stmt::Raise(expr) | Stmt::Yield(expr) => visitor.visit_expr(expr),
+        Stmt::Break(_) | Stmt::Continue(_) => {}
+        Stmt::Assignment { target, value } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        Stmt::ExprStmt(expr) => visitor.visit_expr(expr),
+        Stmt::Block(body) => visitor.visit_block(body),
+        Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_block(else_branch);
+            }
+        }
+        Stmt::While {
+            condition, body, ..
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        Stmt::Loop { body, .. } => visitor.visit_block(body),
+        Stmt::For {
+            iter_expr,
+            step,
+            body,
+            ..
+        } => {
+            visitor.visit_expr(iter_expr);
+            if let Some(step) = step {
+                visitor.visit_expr(step);
+            }
+            visitor.visit_block(body);
+        }
+        Stmt::Switch {
+            expr,
+            cases,
+            default,
+        } => {
+            visitor.visit_expr(expr);
+            for (value, body) in cases {
+                visitor.visit_expr(value);
+                visitor.visit_block(body);
+            }
+            if let Some(default) = default {
+                visitor.visit_block(default);
+            }
+        }
+        Stmt::Try { body, handler, .. } => {
+            visitor.visit_block(body);
+            visitor.visit_block(handler);
+        }
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Literal(literal) => visitor.visit_literal(literal),
+        Expr::Variable(_) => {}
+        Expr::UnaryOp { expr, .. } | Expr::Ref { expr, .. } => visitor.visit_expr(expr),
+        Expr::BinaryOp { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        Expr::Compare { first, rest } => {
+            visitor.visit_expr(first);
+            for (_, operand) in rest {
+                visitor.visit_expr(operand);
+            }
+        }
+        Expr::FuncCall { args, kwargs, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::MethodCall {
+            object: callee,
+            args,
+            kwargs,
+            ..
+        }
+        | Expr::Call {
+            callee,
+            args,
+            kwargs,
+        } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            for (_, value) in kwargs {
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::FieldAccess { object, .. } => visitor.visit_expr(object),
+        Expr::ArrayAccess { object, index } => {
+            visitor.visit_expr(object);
+            visitor.visit_expr(index);
+        }
+        Expr::ArrayLiteral(elements) | Expr::Tuple(elements) => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        }
+        Expr::MapLiteral(entries) => {
+            for entry in entries {
+                match entry {
+                    MapEntry::Pair(key, value) => {
+                        visitor.visit_expr(key);
+                        visitor.visit_expr(value);
+                    }
+                    MapEntry::Spread(base) => visitor.visit_expr(base),
+                }
+            }
+        }
+        Expr::Grouped(inner) | Expr::Spread(inner) => visitor.visit_expr(inner),
+        Expr::Lambda { params, body } => {
+            for param in params {
+                if let Some(default) = &mut param.default {
+                    visitor.visit_expr(default);
+                }
+            }
+            visitor.visit_expr(body);
+        }
+        Expr::Do { body, value } => {
+            visitor.visit_block(body);
+            visitor.visit_expr(value);
+        }
+        Expr::ListComp { element, clause } => {
+            walk_comp_clause_mut(visitor, clause);
+            visitor.visit_expr(element);
+        }
+        Expr::MapComp { key, value, clause } => {
+            walk_comp_clause_mut(visitor, clause);
+            visitor.visit_expr(key);
+            visitor.visit_expr(value);
+        }
+    }
+}
+
+fn walk_comp_clause_mut<V: VisitorMut + ?Sized>(visitor: &mut V, clause: &mut CompClause) {
+    visitor.visit_expr(&mut clause.iter);
+    if let Some(condition) = &mut clause.condition {
+        visitor.visit_expr(condition);
+    }
+}
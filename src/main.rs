@@ -95,6 +95,11 @@ fn main() {
                 let large: bool = true;
         }
         
+        # Swapping through a tuple; the `(` starts a new statement, not a call
+        let a = 1
+        let b = 2
+        (a, b) = (b, a)
+        
         # Complex expressions with all operators
         let complexResult: i32 = ((x + y) * 2 - 5) / (add(3, 4) + 1);
         let comparison: bool = (x >= y) && (result1 != result2);
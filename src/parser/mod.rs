@@ -6,7 +6,7 @@ use pest::pratt_parser::{Assoc, Op, PrattParser};
 #[grammar = "widow.pest"] // relative to src/
 pub struct WidowParser;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expr {
     Literal(String),
     Variable(String),
@@ -14,14 +14,36 @@ pub enum Expr {
         op: String,
         expr: Box<Expr>,
     },
+    Ref {
+        mutable: bool,
+        expr: Box<Expr>,
+    },
     BinaryOp {
         left: Box<Expr>,
         op: String,
         right: Box<Expr>,
     },
+    // `a < b <= c` holds when every adjacent pair does; each operand is
+    // evaluated once, left to right, stopping at the first pair that fails
+    Compare {
+        first: Box<Expr>,
+        rest: Vec<(String, Expr)>,
+    },
     FuncCall {
         name: String,
         args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>, // `f(name: value)`
+    },
+    MethodCall {
+        object: Box<Expr>, // an instance, or a type name for associated functions
+        method: String,
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
+    },
+    Call {
+        callee: Box<Expr>, // any other expression: `make(1)(2)`, `fs[0](x)`
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
     },
     FieldAccess {
         object: Box<Expr>,
@@ -32,8 +54,44 @@ pub enum Expr {
         index: Box<Expr>,
     },
     ArrayLiteral(Vec<Expr>),
-    MapLiteral(Vec<(Expr, Expr)>),
+    MapLiteral(Vec<MapEntry>),
+    Tuple(Vec<Expr>),
     Grouped(Box<Expr>),
+    Spread(Box<Expr>), // `xs...`
+    Lambda {
+        params: Vec<(String, Option<String>)>, // `|x, y: i64| ...`
+        body: Box<Expr>,
+    },
+    // `do { stmt; ...; value }`. Statements are kept as source text, as the
+    // statement parsers have no tree of their own yet
+    Do {
+        body: Vec<String>,
+        value: Box<Expr>,
+    },
+    ListComp {
+        element: Box<Expr>,
+        clause: CompClause,
+    },
+    MapComp {
+        key: Box<Expr>,
+        value: Box<Expr>,
+        clause: CompClause,
+    },
+}
+
+// `for var, value_var in iter if condition`, shared by the comprehensions
+#[derive(Debug, Clone)]
+pub struct CompClause {
+    pub var: String,
+    pub value_var: Option<String>,
+    pub iter: Box<Expr>,
+    pub condition: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MapEntry {
+    Pair(Expr, Expr),
+    Spread(Expr), // `{base..., "extra": 1}`
 }
 
 lazy_static::lazy_static! {
@@ -58,9 +116,10 @@ lazy_static::lazy_static! {
     };
 }
 
-pub fn parse_source(source: &str) -> Result<(), pest::error::Error<Rule>> {
-    let mut parsed = WidowParser::parse(Rule::program, source)?;
+pub fn parse_source(source: &str) -> Result<(), Box<pest::error::Error<Rule>>> {
+    let mut parsed = WidowParser::parse(Rule::program, source).map_err(Box::new)?;
     let program = parsed.next().unwrap();
+    check_escapes(&program)?;
 
     for stmt in program.into_inner() {
         if stmt.as_rule() == Rule::EOI {
@@ -144,13 +203,13 @@ fn parse_expression(pair: Pair<Rule>) -> Expr {
         Rule::logical_or => parse_binary_expr(pair),
         Rule::logical_and => parse_binary_expr(pair),
         Rule::equality => parse_binary_expr(pair),
-        Rule::comparison => parse_binary_expr(pair),
+        Rule::comparison => parse_comparison_expr(pair),
         Rule::range => parse_binary_expr(pair),
         Rule::addition => parse_binary_expr(pair),
         Rule::multiplication => parse_binary_expr(pair),
         Rule::unary => parse_unary_expr(pair),
         Rule::postfix => parse_postfix_expr(pair),
-        Rule::primary => parse_primary(pair),
+        Rule::primary => parse_primary(pair.into_inner().next().unwrap()),
         _ => {
             // If it's a direct atom, parse it
             parse_primary(pair)
@@ -174,13 +233,41 @@ fn parse_binary_expr(pair: Pair<Rule>) -> Expr {
     left
 }
 
+// `a < b <= c` is a chain, not `(a < b) <= c`
+fn parse_comparison_expr(pair: Pair<Rule>) -> Expr {
+    let mut inner = pair.into_inner();
+    let first = parse_expression(inner.next().unwrap());
+
+    let mut rest = Vec::new();
+    while let Some(op_pair) = inner.next() {
+        let operand = parse_expression(inner.next().unwrap());
+        rest.push((get_binary_op_string(&op_pair), operand));
+    }
+
+    match rest.len() {
+        0 => first,
+        1 => {
+            let (op, right) = rest.pop().unwrap();
+            Expr::BinaryOp {
+                left: Box::new(first),
+                op,
+                right: Box::new(right),
+            }
+        }
+        _ => Expr::Compare {
+            first: Box::new(first),
+            rest,
+        },
+    }
+}
+
 fn parse_unary_expr(pair: Pair<Rule>) -> Expr {
     let mut inner = pair.into_inner();
     let mut ops = Vec::new();
 
     // Collect all unary operators
     while let Some(next) = inner.peek() {
-        if matches!(next.as_rule(), Rule::unary) {
+        if matches!(next.as_rule(), Rule::unary_op) {
             ops.push(inner.next().unwrap().as_str().to_string());
         } else {
             break;
@@ -192,9 +279,15 @@ fn parse_unary_expr(pair: Pair<Rule>) -> Expr {
 
     // Apply unary operators (right to left)
     for op in ops.into_iter().rev() {
-        expr = Expr::UnaryOp {
-            op,
-            expr: Box::new(expr),
+        expr = match op.as_str() {
+            "&" | "&mut" => Expr::Ref {
+                mutable: op == "&mut",
+                expr: Box::new(expr),
+            },
+            _ => Expr::UnaryOp {
+                op,
+                expr: Box::new(expr),
+            },
         };
     }
 
@@ -205,22 +298,39 @@ fn parse_postfix_expr(pair: Pair<Rule>) -> Expr {
     let mut inner = pair.into_inner();
     let mut expr = parse_expression(inner.next().unwrap());
 
-    for postfix_op in inner {
+    for op_pair in inner {
+        // postfix_op wraps exactly one of the concrete operator rules
+        let postfix_op = op_pair.into_inner().next().unwrap();
         match postfix_op.as_rule() {
             Rule::function_call_op => {
-                let args = if let Some(args_inner) = postfix_op.into_inner().next() {
-                    args_inner.into_inner().map(parse_expression).collect()
-                } else {
-                    Vec::new()
+                let mut args = Vec::new();
+                let mut kwargs = Vec::new();
+                for arg in postfix_op.into_inner() {
+                    match arg.as_rule() {
+                        Rule::keyword_arg => {
+                            let mut kw = arg.into_inner();
+                            let name = kw.next().unwrap().as_str().to_string();
+                            kwargs.push((name, parse_expression(kw.next().unwrap())));
+                        }
+                        _ => args.push(parse_spreadable(arg)),
+                    }
+                }
+
+                // `obj.method(...)` and `Type.func(...)` call through the field
+                expr = match expr {
+                    Expr::Variable(name) => Expr::FuncCall { name, args, kwargs },
+                    Expr::FieldAccess { object, field } => Expr::MethodCall {
+                        object,
+                        method: field,
+                        args,
+                        kwargs,
+                    },
+                    callee => Expr::Call {
+                        callee: Box::new(callee),
+                        args,
+                        kwargs,
+                    },
                 };
-
-                // Extract function name from current expression
-                let name = match expr {
-                    Expr::Variable(n) => n,
-                    _ => "unknown".to_string(), // This shouldn't happen with proper grammar
-                };
-
-                expr = Expr::FuncCall { name, args };
             }
             Rule::field_access_op => {
                 let field = postfix_op.into_inner().next().unwrap().as_str().to_string();
@@ -247,30 +357,146 @@ fn parse_primary(pair: Pair<Rule>) -> Expr {
     match pair.as_rule() {
         Rule::literal => Expr::Literal(pair.as_str().to_string()),
         Rule::identifier => Expr::Variable(pair.as_str().to_string()),
-        Rule::grouped_expr => {
-            let inner = pair.into_inner().next().unwrap();
-            Expr::Grouped(Box::new(parse_expression(inner)))
+        Rule::paren_expr => {
+            let mut inner = pair.into_inner();
+            let first = parse_expression(inner.next().unwrap());
+            match inner.next() {
+                Some(tail) => {
+                    let mut elements = vec![first];
+                    elements.extend(tail.into_inner().map(parse_expression));
+                    Expr::Tuple(elements)
+                }
+                None => Expr::Grouped(Box::new(first)),
+            }
+        }
+        Rule::lambda => {
+            let mut inner: Vec<Pair<Rule>> = pair.into_inner().collect();
+            let body = parse_expression(inner.pop().unwrap());
+            let params = inner
+                .into_iter()
+                .map(|param| {
+                    let mut parts = param.into_inner();
+                    let name = parts.next().unwrap().as_str().to_string();
+                    (name, parts.next().map(|ty| ty.as_str().to_string()))
+                })
+                .collect();
+            Expr::Lambda {
+                params,
+                body: Box::new(body),
+            }
+        }
+        Rule::do_block => {
+            let mut inner: Vec<Pair<Rule>> = pair.into_inner().collect();
+            let value = inner.pop().unwrap().into_inner().next().unwrap();
+            let body = inner
+                .into_iter()
+                .map(|stmt| {
+                    stmt.into_inner()
+                        .next()
+                        .unwrap()
+                        .as_str()
+                        .trim_end()
+                        .to_string()
+                })
+                .collect();
+            Expr::Do {
+                body,
+                value: Box::new(parse_expression(value)),
+            }
         }
         Rule::array_literal => {
-            let elements: Vec<Expr> = pair.into_inner().map(parse_expression).collect();
+            let mut inner = pair.into_inner();
+            let Some(first) = inner.next() else {
+                return Expr::ArrayLiteral(Vec::new());
+            };
+            let mut elements = vec![parse_expression(first)];
+
+            for next in inner {
+                match next.as_rule() {
+                    Rule::comp_clause => {
+                        return Expr::ListComp {
+                            element: Box::new(elements.pop().unwrap()),
+                            clause: parse_comp_clause(next),
+                        };
+                    }
+                    Rule::spread_op => {
+                        let first = elements.pop().unwrap();
+                        elements.push(Expr::Spread(Box::new(first)));
+                    }
+                    _ => elements.push(parse_spreadable(next)),
+                }
+            }
+
             Expr::ArrayLiteral(elements)
         }
         Rule::map_literal => {
-            let entries: Vec<(Expr, Expr)> = pair
-                .into_inner()
-                .map(|entry_pair| {
-                    let mut entry_inner = entry_pair.into_inner();
-                    let key = parse_expression(entry_inner.next().unwrap());
-                    let value = parse_expression(entry_inner.next().unwrap());
-                    (key, value)
-                })
-                .collect();
+            let mut inner = pair.into_inner().peekable();
+            let Some(first) = inner.next() else {
+                return Expr::MapLiteral(Vec::new());
+            };
+            let key = parse_expression(first);
+            let next = inner.next().unwrap();
+            let first = match next.as_rule() {
+                Rule::spread_op => MapEntry::Spread(key),
+                _ => {
+                    let value = parse_expression(next);
+                    if let Some(clause) = inner.next_if(|p| p.as_rule() == Rule::comp_clause) {
+                        return Expr::MapComp {
+                            key: Box::new(key),
+                            value: Box::new(value),
+                            clause: parse_comp_clause(clause),
+                        };
+                    }
+                    MapEntry::Pair(key, value)
+                }
+            };
+
+            let mut entries = vec![first];
+            entries.extend(inner.map(parse_map_item));
             Expr::MapLiteral(entries)
         }
         _ => unreachable!("Unexpected primary rule: {:?}", pair.as_rule()),
     }
 }
 
+// A call argument or array element: `expr`, or `expr...` to unpack it
+fn parse_spreadable(pair: Pair<Rule>) -> Expr {
+    let mut inner = pair.into_inner();
+    let expr = parse_expression(inner.next().unwrap());
+    match inner.next() {
+        Some(_) => Expr::Spread(Box::new(expr)),
+        None => expr,
+    }
+}
+
+// `key: value` or `map...` after the first entry of a map literal
+fn parse_map_item(pair: Pair<Rule>) -> MapEntry {
+    let mut inner = pair.into_inner();
+    let key = parse_expression(inner.next().unwrap());
+    let next = inner.next().unwrap();
+    match next.as_rule() {
+        Rule::spread_op => MapEntry::Spread(key),
+        _ => MapEntry::Pair(key, parse_expression(next)),
+    }
+}
+
+fn parse_comp_clause(pair: Pair<Rule>) -> CompClause {
+    let mut inner = pair
+        .into_inner()
+        .filter(|part| !matches!(part.as_rule(), Rule::for_kw | Rule::in_kw | Rule::if_kw));
+    let mut vars = inner.next().unwrap().into_inner();
+    let var = vars.next().unwrap().as_str().to_string();
+    let value_var = vars.next().map(|var| var.as_str().to_string());
+    let iter = parse_expression(inner.next().unwrap());
+    let condition = inner.next().map(|cond| Box::new(parse_expression(cond)));
+    CompClause {
+        var,
+        value_var,
+        iter: Box::new(iter),
+        condition,
+    }
+}
+
 fn get_binary_op_string(pair: &Pair<Rule>) -> String {
     // The binary operators are now embedded in the grammar rules
     // We need to extract the actual operator string
@@ -283,6 +509,7 @@ fn get_binary_op_string(pair: &Pair<Rule>) -> String {
         s if s.contains(">=") => ">=".to_string(),
         s if s.contains("<") => "<".to_string(),
         s if s.contains(">") => ">".to_string(),
+        s if s.contains("..=") => "..=".to_string(),
         s if s.contains("..") => "..".to_string(),
         s if s.contains("+") => "+".to_string(),
         s if s.contains("-") => "-".to_string(),
@@ -293,6 +520,82 @@ fn get_binary_op_string(pair: &Pair<Rule>) -> String {
     }
 }
 
+/// Decodes the escape sequences in the body of a string or char literal
+/// (the text between the quotes). The grammar only admits well-formed
+/// escapes, so for parsed input the only failure is a `\u{...}` that is
+/// not a valid scalar value, such as a surrogate; `parse_source` reports
+/// that as a parse error.
+pub fn unescape(body: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('0') => out.push('\0'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let code = Some(hex.as_str())
+                    .filter(|hex| hex.len() == 2)
+                    .and_then(hex_value)
+                    .filter(|&code| code < 0x80)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid hex escape: \\x{}", hex))?;
+                out.push(code);
+            }
+            Some('u') => {
+                let (hex, rest) = chars
+                    .as_str()
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .ok_or_else(|| "unterminated unicode escape".to_string())?;
+                let code = Some(hex)
+                    .filter(|hex| hex.len() <= 6)
+                    .and_then(hex_value)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape: \\u{{{}}}", hex))?;
+                out.push(code);
+                chars = rest.chars();
+            }
+            Some(c @ ('\\' | '"' | '\'')) => out.push(c),
+            Some(other) => return Err(format!("unknown escape sequence: \\{}", other)),
+            None => return Err("trailing backslash in literal".to_string()),
+        }
+    }
+
+    Ok(out)
+}
+
+// `from_str_radix` alone would also accept a sign, as in `\x+4`
+fn hex_value(digits: &str) -> Option<u32> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+// The grammar only checks the shape of an escape, so values that are not
+// valid characters are reported here, at the literal's span
+fn check_escapes(program: &Pair<Rule>) -> Result<(), Box<pest::error::Error<Rule>>> {
+    for pair in program.clone().into_inner().flatten() {
+        if matches!(pair.as_rule(), Rule::string | Rule::char) {
+            let text = pair.as_str();
+            unescape(&text[1..text.len() - 1]).map_err(|message| {
+                let variant = pest::error::ErrorVariant::CustomError { message };
+                Box::new(pest::error::Error::new_from_span(variant, pair.as_span()))
+            })?;
+        }
+    }
+    Ok(())
+}
+
 // Helper functions for parsing different statement types
 fn parse_variable_decl(pair: Pair<Rule>) {
     println!("Parsing variable declaration:");
@@ -369,3 +672,222 @@ fn parse_control_flow(pair: Pair<Rule>) {
         _ => println!("  unknown control flow: {:?}", inner.as_rule()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expr(source: &str) -> Expr {
+        let mut parsed = WidowParser::parse(Rule::expression, source).unwrap();
+        parse_expression(parsed.next().unwrap())
+    }
+
+    fn statements(source: &str) -> Vec<Rule> {
+        WidowParser::parse(Rule::program, source)
+            .unwrap()
+            .next()
+            .unwrap()
+            .into_inner()
+            .filter(|pair| pair.as_rule() == Rule::statement)
+            .map(|pair| pair.into_inner().next().unwrap().as_rule())
+            .collect()
+    }
+
+    #[test]
+    fn call_parens_stay_on_the_callee_line() {
+        let rules = statements("let a = 1\nlet b = 2\n(a, b) = (b, a)\n");
+        assert_eq!(
+            rules,
+            [
+                Rule::variable_decl,
+                Rule::variable_decl,
+                Rule::assignment_stmt
+            ]
+        );
+
+        let rules = statements("f (1)\nxs\n  .map(f)\n");
+        assert_eq!(rules, [Rule::expr_stmt, Rule::expr_stmt]);
+    }
+
+    #[test]
+    fn elif_needs_a_word_boundary() {
+        assert!(WidowParser::parse(Rule::program, "if a { b() } else if c { d() }").is_ok());
+        assert!(WidowParser::parse(Rule::program, "if a { b() } else iffy { d() }").is_err());
+        assert!(WidowParser::parse(Rule::program, "if a { b() } elifc { d() }").is_err());
+    }
+
+    #[test]
+    fn for_and_in_need_a_word_boundary() {
+        assert!(WidowParser::parse(Rule::program, "for x in xs { f(x) }").is_ok());
+        assert!(WidowParser::parse(Rule::program, "formula in xs { f() }").is_err());
+        assert!(WidowParser::parse(Rule::program, "for x inside { f(x) }").is_err());
+        assert!(WidowParser::parse(Rule::program, "let a = [x formula in xs]").is_err());
+        assert!(WidowParser::parse(Rule::program, "let a = [x for x inxs]").is_err());
+    }
+
+    #[test]
+    fn if_needs_a_word_boundary() {
+        assert!(WidowParser::parse(Rule::program, "if a { b() }").is_ok());
+        assert!(WidowParser::parse(Rule::program, "iffy { b() }").is_err());
+        assert!(WidowParser::parse(Rule::program, "let a = [x for x in xs if ok]").is_ok());
+        assert!(WidowParser::parse(Rule::program, "let a = [x for x in xs iffy]").is_err());
+    }
+
+    #[test]
+    fn catch_needs_a_word_boundary() {
+        let source = "try { f() } catch err { g(err) }";
+        assert!(WidowParser::parse(Rule::program, source).is_ok());
+        let source = "try { f() } catchall { g() }";
+        assert!(WidowParser::parse(Rule::program, source).is_err());
+    }
+
+    #[test]
+    fn chains_comparisons() {
+        let Expr::Compare { first, rest } = expr("0 <= x < n") else {
+            panic!("not a comparison chain");
+        };
+        assert!(matches!(*first, Expr::Literal(ref n) if n == "0"));
+        let ops: Vec<_> = rest.iter().map(|(op, _)| op.as_str()).collect();
+        assert_eq!(ops, ["<=", "<"]);
+
+        // A single comparison stays a plain binary operation
+        assert!(matches!(expr("x < n"), Expr::BinaryOp { op, .. } if op == "<"));
+    }
+
+    #[test]
+    fn lambdas_keep_parameter_types() {
+        let Expr::Lambda { params, body } = expr("|x, y: i64| x + y") else {
+            panic!("not a lambda");
+        };
+        assert_eq!(
+            params,
+            [
+                ("x".to_string(), None),
+                ("y".to_string(), Some("i64".to_string()))
+            ]
+        );
+        assert!(matches!(*body, Expr::BinaryOp { ref op, .. } if op == "+"));
+
+        assert!(matches!(expr("|| 1"), Expr::Lambda { params, .. } if params.is_empty()));
+    }
+
+    #[test]
+    fn lambdas_can_be_called_and_passed() {
+        let Expr::Call { callee, args, .. } = expr("(|x| x * 2)(3)") else {
+            panic!("not a call");
+        };
+        let Expr::Grouped(callee) = *callee else {
+            panic!("callee is not grouped");
+        };
+        assert!(matches!(*callee, Expr::Lambda { .. }));
+        assert_eq!(args.len(), 1);
+
+        let Expr::MethodCall { method, args, .. } = expr("xs.map(|x: i64| x + 1)") else {
+            panic!("not a method call");
+        };
+        assert_eq!(method, "map");
+        assert!(matches!(&args[..], [Expr::Lambda { params, .. }] if params[0].1.is_some()));
+    }
+
+    #[test]
+    fn do_blocks_keep_their_statements() {
+        let Expr::Do { body, value } = expr("do {\n    let a = f()\n    g(a);\n    a + 1\n}")
+        else {
+            panic!("not a do block");
+        };
+        assert_eq!(body, ["let a = f()", "g(a)"]);
+        assert!(matches!(*value, Expr::BinaryOp { ref op, .. } if op == "+"));
+    }
+
+    #[test]
+    fn parses_list_comprehensions() {
+        let Expr::ListComp { element, clause } = expr("[x * 2 for x in xs if x > 0]") else {
+            panic!("not a list comprehension");
+        };
+        assert!(matches!(*element, Expr::BinaryOp { ref op, .. } if op == "*"));
+        assert_eq!(clause.var, "x");
+        assert_eq!(clause.value_var, None);
+        assert!(matches!(*clause.iter, Expr::Variable(ref name) if name == "xs"));
+        assert!(
+            matches!(clause.condition.as_deref(), Some(Expr::BinaryOp { op, .. }) if op == ">")
+        );
+    }
+
+    #[test]
+    fn parses_map_comprehensions() {
+        let Expr::MapComp { key, value, clause } = expr("{k: v + 1 for k, v in pairs}") else {
+            panic!("not a map comprehension");
+        };
+        assert!(matches!(*key, Expr::Variable(ref name) if name == "k"));
+        assert!(matches!(*value, Expr::BinaryOp { ref op, .. } if op == "+"));
+        assert_eq!(clause.var, "k");
+        assert_eq!(clause.value_var.as_deref(), Some("v"));
+        assert!(matches!(*clause.iter, Expr::Variable(ref name) if name == "pairs"));
+        assert!(clause.condition.is_none());
+    }
+
+    #[test]
+    fn parses_spreads() {
+        let Expr::ArrayLiteral(elements) = expr("[1, xs..., f(ys...)]") else {
+            panic!("not an array literal");
+        };
+        assert!(matches!(
+            &elements[..],
+            [Expr::Literal(_), Expr::Spread(_), Expr::FuncCall { args, .. }]
+                if matches!(&args[..], [Expr::Spread(_)])
+        ));
+
+        let Expr::MapLiteral(entries) = expr("{base..., \"extra\": 1, more...}") else {
+            panic!("not a map literal");
+        };
+        assert!(matches!(
+            &entries[..],
+            [MapEntry::Spread(_), MapEntry::Pair(..), MapEntry::Spread(_)]
+        ));
+    }
+
+    #[test]
+    fn keyword_arguments_follow_positional_ones() {
+        assert!(WidowParser::parse(Rule::program, "f(1, x, a: 2, b: 3)").is_ok());
+        assert!(WidowParser::parse(Rule::program, "f(a: 1, 2)").is_err());
+    }
+
+    #[test]
+    fn unescapes_literal_bodies() {
+        assert_eq!(unescape(r"a\tb\n").unwrap(), "a\tb\n");
+        assert_eq!(unescape(r#"\"\\\'\0"#).unwrap(), "\"\\'\0");
+        assert_eq!(unescape(r"\x41\u{1F600}!").unwrap(), "A\u{1F600}!");
+    }
+
+    #[test]
+    fn rejects_malformed_escapes() {
+        for body in [r"\x+4", r"\x4", r"\x4g", r"\x80", r"\u{+41}", r"\u{}"] {
+            assert!(unescape(body).is_err(), "{} was accepted", body);
+        }
+        assert_eq!(
+            unescape(r"\u{D800}").unwrap_err(),
+            r"invalid unicode escape: \u{D800}"
+        );
+        assert_eq!(
+            unescape(r"\u{1234567}").unwrap_err(),
+            r"invalid unicode escape: \u{1234567}"
+        );
+        assert_eq!(
+            unescape(r"\u{41").unwrap_err(),
+            "unterminated unicode escape"
+        );
+        assert_eq!(unescape(r"\q").unwrap_err(), r"unknown escape sequence: \q");
+        assert_eq!(unescape("\\").unwrap_err(), "trailing backslash in literal");
+    }
+
+    #[test]
+    fn parse_source_checks_escapes() {
+        assert!(parse_source(r#"let s = "\u{41}""#).is_ok());
+        let error = parse_source(r#"let s = "\u{D800}""#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains(r"invalid unicode escape: \u{D800}")
+        );
+    }
+}